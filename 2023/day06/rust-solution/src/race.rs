@@ -1,9 +1,43 @@
+use std::ops::RangeInclusive;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Record {
     pub time: u64,
     pub distance: u64,
 }
 
+impl Record {
+    /// Holding the button for `hold` ms travels `hold * (time - hold)` mm,
+    /// so the record is beaten between the roots of `hold² - time * hold + distance`.
+    /// The roots are found with an integer square root and nudged onto the
+    /// exact boundary, which matters when they fall on whole numbers.
+    pub fn winning_holds(&self) -> Option<RangeInclusive<u64>> {
+        let (time, distance) = (self.time as u128, self.distance as u128);
+        let beats = |hold: u128| hold * (time - hold) > distance;
+
+        let discriminant = (time * time).checked_sub(4 * distance)?;
+        // the furthest any hold goes is at `time / 2`
+        let mut lo = (time - discriminant.isqrt()) / 2;
+        while lo <= time / 2 && !beats(lo) {
+            lo += 1;
+        }
+        if lo > time / 2 {
+            return None;
+        }
+        while lo > 0 && beats(lo - 1) {
+            lo -= 1;
+        }
+
+        // the distances are symmetric around `time / 2`
+        Some(lo as u64..=(time - lo) as u64)
+    }
+
+    pub fn ways_to_win(&self) -> u64 {
+        self.winning_holds()
+            .map_or(0, |holds| holds.end() - holds.start() + 1)
+    }
+}
+
 #[derive(thiserror::Error, Debug)]
 pub enum ParseRecordErr {
     #[error("can not recognized the record format")]
@@ -11,6 +45,12 @@ pub enum ParseRecordErr {
 
     #[error("the record contains a mismatching number of races ({0} times and {1} distances)")]
     RecordMismatch(usize, usize),
+
+    #[error("the record contains no races")]
+    NoRaces,
+
+    #[error("the joined number {0} is too large")]
+    Overflow(String),
 }
 
 pub fn parse_record_sheet(raw: &str) -> Result<Vec<Record>, ParseRecordErr> {
@@ -50,6 +90,9 @@ pub fn parse_record_sheet(raw: &str) -> Result<Vec<Record>, ParseRecordErr> {
 
 pub fn parse_record_sheet_correctly(raw: &str) -> Result<Record, ParseRecordErr> {
     let records = parse_record_sheet(raw)?;
+    if records.is_empty() {
+        return Err(ParseRecordErr::NoRaces);
+    }
 
     let (time, distance) = records.iter().fold(
         (String::new(), String::new()),
@@ -61,15 +104,17 @@ pub fn parse_record_sheet_correctly(raw: &str) -> Result<Record, ParseRecordErr>
         },
     );
 
+    // the numbers are made of digits only, so they can only fail by overflowing
+    let parse = |num: String| num.parse().map_err(|_| ParseRecordErr::Overflow(num));
     Ok(Record {
-        time: time.parse().unwrap(),
-        distance: distance.parse().unwrap(),
+        time: parse(time)?,
+        distance: parse(distance)?,
     })
 }
 
 #[cfg(test)]
 mod tests {
-    use super::Record;
+    use super::{ParseRecordErr, Record};
 
     #[test]
     fn parse_record_sheet() {
@@ -93,4 +138,81 @@ Distance:  9  40  200"#;
         let output = super::parse_record_sheet(input).unwrap();
         assert_eq!(output, expected_output);
     }
+
+    #[test]
+    fn parse_record_sheet_correctly() {
+        let input = r#"Time:      7  15   30
+Distance:  9  40  200"#;
+        let output = super::parse_record_sheet_correctly(input).unwrap();
+        assert_eq!(
+            output,
+            Record {
+                time: 71530,
+                distance: 940200,
+            }
+        );
+
+        let input = r#"Time:      18446744073  709551616
+Distance:  9  40"#;
+        assert!(matches!(
+            super::parse_record_sheet_correctly(input),
+            Err(ParseRecordErr::Overflow(_))
+        ));
+        assert!(matches!(
+            super::parse_record_sheet_correctly("Time:\nDistance:"),
+            Err(ParseRecordErr::NoRaces)
+        ));
+    }
+
+    #[test]
+    fn winning_holds() {
+        let record = |time, distance| Record { time, distance };
+
+        assert_eq!(record(7, 9).winning_holds(), Some(2..=5));
+        assert_eq!(record(15, 40).winning_holds(), Some(4..=11));
+        // the roots are exactly 10 and 20, which only tie the record
+        assert_eq!(record(30, 200).winning_holds(), Some(11..=19));
+        assert_eq!(record(4, 3).winning_holds(), Some(2..=2));
+        // the best hold only ties the record
+        assert_eq!(record(4, 4).winning_holds(), None);
+        assert_eq!(record(5, 6).winning_holds(), None);
+        assert_eq!(record(4, 5).winning_holds(), None);
+        assert_eq!(record(3, 0).winning_holds(), Some(1..=2));
+    }
+
+    #[test]
+    fn winning_holds_matches_brute_force() {
+        for time in 0..60 {
+            for distance in 0..(time * time / 4 + 2) {
+                let record = Record { time, distance };
+                let wins = (0..=time)
+                    .filter(|hold| hold * (time - hold) > distance)
+                    .collect::<Vec<_>>();
+
+                let expected = wins.first().map(|&lo| lo..=*wins.last().unwrap());
+                assert_eq!(record.winning_holds(), expected, "{:?}", record);
+                assert_eq!(record.ways_to_win(), wins.len() as u64);
+            }
+        }
+    }
+
+    #[test]
+    fn winning_holds_near_overflow() {
+        // the furthest hold travels 2^64 mm, one more than the record
+        let record = Record {
+            time: 1 << 33,
+            distance: u64::MAX,
+        };
+        assert_eq!(record.winning_holds(), Some(1 << 32..=1 << 32));
+
+        let time = u64::MAX;
+        let record = Record {
+            time,
+            distance: u64::MAX,
+        };
+        let holds = record.winning_holds().unwrap();
+        let beats = |hold: u64| hold as u128 * (time - hold) as u128 > u64::MAX as u128;
+        assert!(beats(*holds.start()) && !beats(holds.start() - 1));
+        assert!(beats(*holds.end()) && !beats(holds.end() + 1));
+    }
 }
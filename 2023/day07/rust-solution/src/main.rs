@@ -1,24 +1,35 @@
 use poker::{Hand, ParseHandErr};
+use rules::Variant;
 
 mod poker;
+mod rules;
+
+// ranks the hands, and pays every bid times the rank of its hand
+fn total_winnings(mut hands: Vec<Hand>) -> u32 {
+    hands.sort();
+    if let [.., runner_up, best] = hands.as_slice() {
+        tracing::debug!("{}", best.explain(runner_up));
+    }
+
+    hands
+        .into_iter()
+        .enumerate()
+        .map(|(idx, hand)| hand.bid * (idx as u32 + 1))
+        .sum()
+}
 
 fn part_1(input: &str) -> Result<u32, ParseHandErr> {
-    let mut hands = input
+    let hands = input
         .trim()
         .lines()
         .map(|hand| hand.parse::<Hand>())
         .collect::<Result<Vec<_>, _>>()?;
 
-    hands.sort();
-    Ok(hands
-        .into_iter()
-        .enumerate()
-        .map(|(idx, hand)| hand.bid * (idx as u32 + 1))
-        .sum())
+    Ok(total_winnings(hands))
 }
 
 fn part_2(input: &str) -> Result<u32, ParseHandErr> {
-    let mut hands = input
+    let hands = input
         .trim()
         .lines()
         .map(|hand| {
@@ -29,16 +40,31 @@ fn part_2(input: &str) -> Result<u32, ParseHandErr> {
         })
         .collect::<Result<Vec<_>, _>>()?;
 
-    hands.sort();
-    Ok(hands
-        .into_iter()
-        .enumerate()
-        .map(|(idx, hand)| hand.bid * (idx as u32 + 1))
-        .sum())
+    Ok(total_winnings(hands))
+}
+
+/// The total winnings when playing under rules of our own
+fn variant(input: &str, rules: &Variant) -> Result<u32, ParseHandErr> {
+    let hands = input
+        .trim()
+        .lines()
+        .map(|hand| Hand::parse(hand, rules))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(total_winnings(hands))
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let input = rust_shared_utils::read_puzzle()?;
+    tracing_subscriber::fmt::init();
+    let (input, args) = rust_shared_utils::read_puzzle_with_args()?;
+
+    // plays the hands under the rules given after the flag, such as `wilds=2J size=6`
+    if let Some(idx) = args.iter().position(|arg| arg == "--variant") {
+        let rules: Variant = args.get(idx + 1).map_or("", String::as_str).parse()?;
+        println!("total winnings = {}", variant(&input, &rules)?);
+        return Ok(());
+    }
+
     println!("part_1 = {}", part_1(&input)?);
     println!("part_2 = {}", part_2(&input)?);
 
@@ -62,4 +88,13 @@ QQQJA 483"#;
     fn part_2() {
         assert_eq!(super::part_2(INPUT).unwrap(), 5905);
     }
+
+    #[test]
+    fn variant() {
+        let jokers = "wilds=J".parse().unwrap();
+        assert_eq!(super::variant(INPUT, &jokers).unwrap(), 5905);
+
+        let sixes = "size=6".parse().unwrap();
+        assert!(super::variant(INPUT, &sixes).is_err());
+    }
 }
@@ -1,10 +1,10 @@
-use std::{collections::HashMap, str::FromStr};
+use std::{
+    cmp::Ordering,
+    fmt::{self, Display},
+    str::FromStr,
+};
 
-const STENGTHS: [char; 13] = [
-    '2', '3', '4', '5', '6', '7', '8', '9', 'T', 'J', 'Q', 'K', 'A',
-];
-
-type RawHand = [char; 5];
+use crate::rules::{Jokers, Ruleset, Standard};
 
 #[derive(thiserror::Error, Debug)]
 pub enum ParseHandErr {
@@ -21,140 +21,226 @@ pub enum ParseHandErr {
     UnknownCard(char),
 }
 
+/// A hand, ranked under the rules it was dealt with
 #[derive(Debug, Clone, Eq)]
 pub struct Hand {
-    hand: RawHand,
+    cards: Vec<char>,
     ty: HandType,
+    /// the strength of every card, to break ties between hands of the same type
+    strengths: Vec<usize>,
     pub bid: u32,
-    jokers: bool,
 }
 
 impl Hand {
-    fn new(hand: RawHand, ty: HandType, bid: u32) -> Self {
+    pub fn parse(s: &str, rules: &dyn Ruleset) -> Result<Self, ParseHandErr> {
+        let s = s.trim().split_ascii_whitespace().collect::<Vec<_>>();
+        if s.len() != 2 {
+            return Err(ParseHandErr::UnknownFormat);
+        }
+
+        let bid = s[1].parse().map_err(|_| ParseHandErr::BadBidFormat)?;
+        let cards = s[0].chars().collect::<Vec<_>>();
+        if cards.len() != rules.hand_size() {
+            return Err(ParseHandErr::BadHandFormat);
+        }
+
+        if let Some(ch) = cards.iter().find(|&&ch| !rules.is_card(ch)) {
+            // the hand contains an unknwon card
+            return Err(ParseHandErr::UnknownCard(*ch));
+        }
+
+        Ok(Self::new(cards, bid, rules))
+    }
+
+    fn new(cards: Vec<char>, bid: u32, rules: &dyn Ruleset) -> Self {
         Self {
-            hand,
-            ty,
+            ty: rules.classify(&cards),
+            strengths: cards.iter().map(|&card| rules.strength(card)).collect(),
+            cards,
             bid,
-            jokers: false,
         }
     }
 
+    /// The same hand, ranked under other rules
+    pub fn with_rules(&self, rules: &dyn Ruleset) -> Self {
+        Self::new(self.cards.clone(), self.bid, rules)
+    }
+
     /// Enable jokers & recalculate the hand's type
     pub fn enable_joker_cards(&mut self) {
-        self.ty = HandType::from_raw(&self.hand, true);
-        self.jokers = true;
+        *self = self.with_rules(&Jokers);
+    }
+
+    /// Why the hand wins, loses or ties against the other one
+    pub fn explain(&self, other: &Self) -> Explanation {
+        let (winner, loser) = match self.cmp(other) {
+            Ordering::Less => (other, self),
+            _ => (self, other),
+        };
+
+        let reason = match winner.ty.cmp(&loser.ty) {
+            Ordering::Greater => Reason::HandType {
+                winner: winner.ty,
+                loser: loser.ty,
+            },
+            _ => winner
+                .strengths
+                .iter()
+                .zip(&loser.strengths)
+                .position(|(a, b)| a != b)
+                .map(|position| Reason::Card {
+                    ty: winner.ty,
+                    position,
+                    winner: winner.cards[position],
+                    loser: loser.cards[position],
+                })
+                .unwrap_or(Reason::Tie),
+        };
+
+        Explanation {
+            winner: winner.to_string(),
+            loser: loser.to_string(),
+            reason,
+        }
     }
 }
 
+/// Parses the hand under the standard rules
 impl FromStr for Hand {
     type Err = ParseHandErr;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let s = s.trim().split_ascii_whitespace().collect::<Vec<_>>();
-        if s.len() != 2 {
-            return Err(ParseHandErr::UnknownFormat);
-        }
-
-        let bid = s[1].parse().map_err(|_| ParseHandErr::BadBidFormat)?;
-        let hand: RawHand = s[0]
-            .chars()
-            .collect::<Vec<_>>()
-            .try_into()
-            .map_err(|_| ParseHandErr::BadHandFormat)?;
-
-        if let Some(ch) = hand.iter().find(|ch| !STENGTHS.contains(ch)) {
-            // the hand contains an unknwon card
-            return Err(ParseHandErr::UnknownCard(*ch));
-        }
+        Self::parse(s, &Standard)
+    }
+}
 
-        Ok(Self::new(hand, HandType::from_raw(&hand, false), bid))
+impl Display for Hand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.cards.iter().collect::<String>())
     }
 }
 
 impl Ord for Hand {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        fn hand_into_values(hand: &RawHand, jokers: bool) -> Vec<usize> {
-            hand.iter()
-                .map(|ch| {
-                    if jokers && *ch == 'J' {
-                        0
-                    } else {
-                        STENGTHS.iter().position(|card| card == ch).unwrap() + 1
-                    }
-                })
-                .collect()
-        }
-
-        self.ty.cmp(&other.ty).then(
-            hand_into_values(&self.hand, self.jokers)
-                .cmp(&hand_into_values(&other.hand, self.jokers)),
-        )
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.ty
+            .cmp(&other.ty)
+            .then_with(|| self.strengths.cmp(&other.strengths))
     }
 }
 
 impl PartialOrd for Hand {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         Some(self.cmp(other))
     }
 }
 
 impl PartialEq for Hand {
     fn eq(&self, other: &Self) -> bool {
-        self.cmp(other) == std::cmp::Ordering::Equal
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Reason {
+    /// the winner has a stronger type of hand
+    HandType {
+        winner: HandType,
+        loser: HandType,
+    },
+    /// both are of the same type, and the winner has the
+    /// stronger card at the first position where they differ
+    Card {
+        ty: HandType,
+        position: usize,
+        winner: char,
+        loser: char,
+    },
+    Tie,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Explanation {
+    pub winner: String,
+    pub loser: String,
+    pub reason: Reason,
+}
+
+impl Display for Explanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.reason {
+            Reason::HandType { winner, loser } => write!(
+                f,
+                "{} beats {}, {} beats {}",
+                self.winner, self.loser, winner, loser
+            ),
+            Reason::Card {
+                ty,
+                position,
+                winner,
+                loser,
+            } => write!(
+                f,
+                "{} beats {}, both are {} but card {} is {} against {}",
+                self.winner,
+                self.loser,
+                ty,
+                position + 1,
+                winner,
+                loser
+            ),
+            Reason::Tie => write!(f, "{} ties with {}", self.winner, self.loser),
+        }
     }
 }
 
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
-enum HandType {
+pub enum HandType {
     HighCard = 0,
     OnePair,
     TwoPair,
     ThreeOfAKind,
     FullHouse,
     FourOfAKind,
+    /// five or more, in hands bigger than five cards
     FiveOfAKind,
 }
 
 impl HandType {
-    fn from_raw(hand: &RawHand, jokers: bool) -> Self {
-        // map the hand into a list of counts of each label, sorted
-        let mut map: HashMap<&char, usize> = HashMap::new();
-        for ch in hand {
-            *map.entry(ch).or_default() += 1;
-        }
-        let mut extras = 0usize;
-        if jokers && map.len() > 1 {
-            extras = map.remove(&'J').unwrap_or(0);
+    /// Classifies the hand from the number of copies of each card, sorted from the most common
+    pub fn from_counts(counts: &[usize]) -> Self {
+        match (counts[0], counts.get(1).copied().unwrap_or(0)) {
+            (5.., _) => Self::FiveOfAKind,
+            (4, _) => Self::FourOfAKind,
+            (3, 2..) => Self::FullHouse,
+            (3, _) => Self::ThreeOfAKind,
+            (2, 2) => Self::TwoPair,
+            (2, _) => Self::OnePair,
+            _ => Self::HighCard,
         }
+    }
+}
 
-        let mut hand = map.into_values().collect::<Vec<_>>();
-        hand.sort();
-        hand.reverse();
-        hand[0] += extras; // increase the best label
-
-        // map the type
-        match hand.len() {
-            1 => Self::FiveOfAKind,
-            2 => match (hand[0], hand[1]) {
-                (4, 1) => Self::FourOfAKind,
-                (3, 2) => Self::FullHouse,
-                _ => unreachable!(),
-            },
-            3 => match (hand[0], hand[1]) {
-                (3, _) => Self::ThreeOfAKind,
-                (2, 2) => Self::TwoPair,
-                _ => unreachable!(),
-            },
-            4 => Self::OnePair,
-            5 => Self::HighCard,
-            _ => unreachable!(),
-        }
+impl Display for HandType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::HighCard => "high card",
+            Self::OnePair => "one pair",
+            Self::TwoPair => "two pair",
+            Self::ThreeOfAKind => "three of a kind",
+            Self::FullHouse => "full house",
+            Self::FourOfAKind => "four of a kind",
+            Self::FiveOfAKind => "five of a kind",
+        };
+
+        write!(f, "{}", name)
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{Hand, HandType, RawHand};
+    use crate::rules::{Jokers, Ruleset, Standard, Variant};
+
+    use super::{Hand, HandType};
 
     #[test]
     fn parse_hand_type() {
@@ -162,7 +248,7 @@ mod tests {
             "AAAAA", "AA8AA", "23332", "TTT98", "23432", "A23A4", "23456",
         ]
         .into_iter()
-        .map(|hand| -> RawHand { hand.chars().collect::<Vec<_>>().try_into().unwrap() });
+        .map(|hand| hand.chars().collect::<Vec<_>>());
         let expected_outputs = [
             HandType::FiveOfAKind,
             HandType::FourOfAKind,
@@ -174,7 +260,7 @@ mod tests {
         ];
 
         for (input, expected) in inputs.into_iter().zip(expected_outputs) {
-            let output = HandType::from_raw(&input, false);
+            let output = Standard.classify(&input);
             assert_eq!(output, expected);
         }
     }
@@ -182,15 +268,10 @@ mod tests {
     #[test]
     fn parse_hand() {
         let input = "32T3K 765";
-        let expected_output = Hand {
-            bid: 765,
-            hand: ['3', '2', 'T', '3', 'K'],
-            ty: HandType::OnePair,
-            jokers: false,
-        };
-
         let output: Hand = input.parse().unwrap();
-        assert_eq!(output, expected_output);
+        assert_eq!(output.bid, 765);
+        assert_eq!(output.cards, vec!['3', '2', 'T', '3', 'K']);
+        assert_eq!(output.ty, HandType::OnePair);
     }
 
     #[test]
@@ -219,4 +300,44 @@ mod tests {
         hand.enable_joker_cards();
         assert_eq!(hand.ty, HandType::FourOfAKind);
     }
+
+    #[test]
+    fn explain_wins() {
+        let hand1: Hand = "KK677 0".parse().unwrap();
+        let hand2: Hand = "KTJJT 0".parse().unwrap();
+        let hand3: Hand = "32T3K 0".parse().unwrap();
+
+        assert_eq!(
+            hand2.explain(&hand1).to_string(),
+            "KK677 beats KTJJT, both are two pair but card 2 is K against T"
+        );
+        assert_eq!(
+            hand1.explain(&hand3).to_string(),
+            "KK677 beats 32T3K, two pair beats one pair"
+        );
+
+        let (hand1, hand2) = (hand1.with_rules(&Jokers), hand2.with_rules(&Jokers));
+        assert_eq!(
+            hand1.explain(&hand2).to_string(),
+            "KTJJT beats KK677, four of a kind beats two pair"
+        );
+        assert_eq!(hand1.explain(&hand1).to_string(), "KK677 ties with KK677");
+    }
+
+    #[test]
+    fn variant_rules() {
+        let rules = Variant {
+            card_order: "23456789TJQKA".chars().collect(),
+            wilds: vec!['2', 'J'],
+            hand_size: 6,
+        };
+
+        let hand1 = Hand::parse("2KQJ98 1", &rules).unwrap();
+        let hand2 = Hand::parse("AAKK33 2", &rules).unwrap();
+        assert_eq!(hand1.ty, HandType::ThreeOfAKind);
+        assert_eq!(hand2.ty, HandType::TwoPair);
+        assert!(hand1 > hand2);
+
+        assert!(Hand::parse("AAKK3 2", &rules).is_err());
+    }
 }
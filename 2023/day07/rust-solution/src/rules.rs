@@ -0,0 +1,235 @@
+use std::str::FromStr;
+
+use crate::poker::HandType;
+
+const STRENGTHS: [char; 13] = [
+    '2', '3', '4', '5', '6', '7', '8', '9', 'T', 'J', 'Q', 'K', 'A',
+];
+const JOKER: char = 'J';
+const HAND_SIZE: usize = 5;
+
+#[derive(thiserror::Error, Debug, PartialEq, Eq)]
+pub enum ParseRulesErr {
+    #[error("unknown rule, expected cards=, wilds= or size=: {0}")]
+    UnknownRule(String),
+
+    #[error("failed to parse the hand size: {0}")]
+    BadHandSize(String),
+
+    #[error("the wildcard {0} is not one of the cards")]
+    UnknownWild(char),
+}
+
+/// The rules of a game of Camel Cards
+pub trait Ruleset {
+    /// Every card, from the weakest to the strongest
+    fn card_order(&self) -> &[char];
+
+    /// Wildcards act as whatever card makes the strongest hand type,
+    /// but are weaker than every other card when breaking ties
+    fn is_wild(&self, card: char) -> bool;
+
+    fn hand_size(&self) -> usize;
+
+    fn is_card(&self, card: char) -> bool {
+        self.card_order().contains(&card)
+    }
+
+    /// The strength of the card when breaking ties, higher is stronger
+    fn strength(&self, card: char) -> usize {
+        match self.is_wild(card) {
+            true => 0,
+            false => {
+                self.card_order()
+                    .iter()
+                    .position(|&other| other == card)
+                    .unwrap()
+                    + 1
+            }
+        }
+    }
+
+    /// The wildcards join the most common card, which is always the best choice
+    fn classify(&self, cards: &[char]) -> HandType {
+        let mut counts: Vec<(char, usize)> = vec![];
+        let mut wilds = 0;
+        for &card in cards {
+            if self.is_wild(card) {
+                wilds += 1;
+                continue;
+            }
+
+            match counts.iter_mut().find(|(other, _)| *other == card) {
+                Some((_, count)) => *count += 1,
+                None => counts.push((card, 1)),
+            }
+        }
+
+        let mut counts = counts
+            .into_iter()
+            .map(|(_, count)| count)
+            .collect::<Vec<_>>();
+        counts.sort_unstable_by(|a, b| b.cmp(a));
+        match counts.first_mut() {
+            Some(count) => *count += wilds,
+            None => counts.push(wilds),
+        }
+
+        HandType::from_counts(&counts)
+    }
+}
+
+/// The rules of the first part
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Standard;
+
+impl Ruleset for Standard {
+    fn card_order(&self) -> &[char] {
+        &STRENGTHS
+    }
+
+    fn is_wild(&self, _: char) -> bool {
+        false
+    }
+
+    fn hand_size(&self) -> usize {
+        HAND_SIZE
+    }
+}
+
+/// The rules of the second part, where `J` is a joker
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Jokers;
+
+impl Ruleset for Jokers {
+    fn card_order(&self) -> &[char] {
+        &STRENGTHS
+    }
+
+    fn is_wild(&self, card: char) -> bool {
+        card == JOKER
+    }
+
+    fn hand_size(&self) -> usize {
+        HAND_SIZE
+    }
+}
+
+/// Rules made up on the spot, such as more wildcards or bigger hands
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Variant {
+    pub card_order: Vec<char>,
+    pub wilds: Vec<char>,
+    pub hand_size: usize,
+}
+
+impl Ruleset for Variant {
+    fn card_order(&self) -> &[char] {
+        &self.card_order
+    }
+
+    fn is_wild(&self, card: char) -> bool {
+        self.wilds.contains(&card)
+    }
+
+    fn hand_size(&self) -> usize {
+        self.hand_size
+    }
+}
+
+/// Parses rules such as `cards=23456789TJQKA wilds=2J size=6`,
+/// every rule left out is the same as in the standard rules
+impl FromStr for Variant {
+    type Err = ParseRulesErr;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut variant = Self {
+            card_order: STRENGTHS.to_vec(),
+            wilds: vec![],
+            hand_size: HAND_SIZE,
+        };
+
+        for rule in s.split_ascii_whitespace() {
+            match rule.split_once('=') {
+                Some(("cards", cards)) => variant.card_order = cards.chars().collect(),
+                Some(("wilds", wilds)) => variant.wilds = wilds.chars().collect(),
+                Some(("size", size)) => {
+                    variant.hand_size = size
+                        .parse()
+                        .map_err(|_| ParseRulesErr::BadHandSize(size.into()))?
+                }
+                _ => return Err(ParseRulesErr::UnknownRule(rule.into())),
+            }
+        }
+
+        if let Some(&wild) = variant.wilds.iter().find(|&&wild| !variant.is_card(wild)) {
+            return Err(ParseRulesErr::UnknownWild(wild));
+        }
+
+        Ok(variant)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::poker::HandType;
+
+    use super::{Jokers, ParseRulesErr, Ruleset, Standard, Variant};
+
+    #[test]
+    fn joker_strength() {
+        assert_eq!(Standard.strength('J'), 10);
+        assert_eq!(Jokers.strength('J'), 0);
+        assert_eq!(Jokers.strength('2'), 1);
+    }
+
+    #[test]
+    fn classify_with_wildcards() {
+        let hand = ['Q', 'J', 'J', 'Q', '2'];
+        assert_eq!(Standard.classify(&hand), HandType::TwoPair);
+        assert_eq!(Jokers.classify(&hand), HandType::FourOfAKind);
+        assert_eq!(Jokers.classify(&['J'; 5]), HandType::FiveOfAKind);
+
+        let variant = Variant {
+            card_order: "23456789TJQKA".chars().collect(),
+            wilds: vec!['J', '2'],
+            hand_size: 6,
+        };
+        let hand = ['2', 'J', '3', '4', '5', '6'];
+        assert_eq!(variant.classify(&hand), HandType::ThreeOfAKind);
+        assert_eq!(
+            variant.classify(&['A', 'A', 'A', 'K', 'K', 'K']),
+            HandType::FullHouse
+        );
+    }
+
+    #[test]
+    fn parse_variant() {
+        let variant: Variant = "wilds=2J size=6".parse().unwrap();
+        assert_eq!(
+            variant,
+            Variant {
+                card_order: "23456789TJQKA".chars().collect(),
+                wilds: vec!['2', 'J'],
+                hand_size: 6,
+            }
+        );
+
+        let variant: Variant = "cards=abc".parse().unwrap();
+        assert_eq!(variant.strength('c'), 3);
+        assert_eq!(variant.hand_size(), 5);
+
+        assert_eq!(
+            "size=six".parse::<Variant>(),
+            Err(ParseRulesErr::BadHandSize("six".into()))
+        );
+        assert_eq!(
+            "jokers".parse::<Variant>(),
+            Err(ParseRulesErr::UnknownRule("jokers".into()))
+        );
+        assert_eq!(
+            "cards=abc wilds=x".parse::<Variant>(),
+            Err(ParseRulesErr::UnknownWild('x'))
+        );
+    }
+}
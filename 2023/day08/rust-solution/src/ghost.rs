@@ -0,0 +1,245 @@
+use num::Integer;
+
+use crate::map::{Network, NetworkErr, Pattern};
+
+/// The walk from a start node, which always ends up going around a cycle
+/// of `(node, pattern index)` states once the start is far enough behind
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Walk {
+    /// the steps taken before reaching the cycle
+    pub tail: usize,
+    /// the steps it takes to go around the cycle
+    pub length: usize,
+    /// the steps before the cycle that end on a target node
+    pub tail_hits: Vec<usize>,
+    /// the steps in the first pass of the cycle that end on a target node,
+    /// adding any multiple of `length` to them lands on a target again
+    pub cycle_hits: Vec<usize>,
+}
+
+impl Walk {
+    /// Follows the pattern from `start` until a state repeats itself,
+    /// `jumps` is the network's jump table for the pattern
+    pub fn new(
+        network: &Network,
+        pattern: &Pattern,
+        jumps: &[usize],
+        start: usize,
+        is_target: impl Fn(usize) -> bool,
+    ) -> Self {
+        let moves = pattern.moves();
+
+        // every state at the start of a pass is just a node, so the cycle
+        // is found by skipping whole passes, the pass each node was first seen at
+        let mut seen = vec![None; network.len()];
+        let mut current = start;
+        let (tail_passes, cycle_passes) = (0usize..)
+            .find_map(|pass| {
+                if let Some(first) = seen[current] {
+                    return Some((first, pass - first));
+                }
+                seen[current] = Some(pass);
+                current = jumps[current];
+                None
+            })
+            .unwrap();
+
+        // the cycle may start in the middle of the pass before,
+        // where the walk meets itself one cycle ahead
+        let length = cycle_passes * moves.len();
+        let tail = match tail_passes.checked_sub(1) {
+            None => 0,
+            Some(pass) => {
+                let mut a = network.position_after(pattern, jumps, start, pass * moves.len());
+                let mut b = network.position_after(pattern, jumps, a, length);
+                let offset = moves
+                    .iter()
+                    .position(|&step| {
+                        (a, b) = (network.next(a, step), network.next(b, step));
+                        a == b
+                    })
+                    .map_or(moves.len(), |idx| idx + 1);
+
+                pass * moves.len() + offset
+            }
+        };
+
+        let mut hits = vec![];
+        let mut current = start;
+        for step in 0..tail + length {
+            if is_target(current) {
+                hits.push(step);
+            }
+            current = network.next(current, moves[step % moves.len()]);
+        }
+
+        let (tail_hits, cycle_hits) = hits.into_iter().partition(|&hit| hit < tail);
+        Self {
+            tail,
+            length,
+            tail_hits,
+            cycle_hits,
+        }
+    }
+
+    pub fn is_hit(&self, step: usize) -> bool {
+        match step < self.tail {
+            true => self.tail_hits.contains(&step),
+            false => self
+                .cycle_hits
+                .contains(&(self.tail + (step - self.tail) % self.length)),
+        }
+    }
+
+    /// The first step that ends on a target node
+    pub fn first_hit(&self) -> Option<usize> {
+        self.tail_hits.first().or(self.cycle_hits.first()).copied()
+    }
+}
+
+/// Merges `step = a.0 (mod a.1)` and `step = b.0 (mod b.1)`
+/// into a single congruence, the moduli don't have to be coprime
+pub fn crt((r1, m1): (i128, i128), (r2, m2): (i128, i128)) -> Option<(i128, i128)> {
+    let gcd = m1.extended_gcd(&m2);
+    if (r2 - r1) % gcd.gcd != 0 {
+        return None;
+    }
+
+    let lcm = m1 / gcd.gcd * m2;
+    let k = ((r2 - r1) / gcd.gcd * gcd.x).rem_euclid(m2 / gcd.gcd);
+    Some(((r1 + m1 * k).rem_euclid(lcm), lcm))
+}
+
+/// The first step where every walk ends on a target node at the same time
+pub fn first_common_hit(walks: &[Walk]) -> Result<usize, NetworkErr> {
+    // a hit while some walk is still on its tail must be one of its tail hits
+    let tail_hit = walks
+        .iter()
+        .flat_map(|walk| walk.tail_hits.iter().copied())
+        .filter(|&step| walks.iter().all(|walk| walk.is_hit(step)))
+        .min();
+
+    // after every tail, each walk contributes one congruence per cycle hit
+    let start = walks.iter().map(|walk| walk.tail).max().unwrap_or(0);
+    let mut congruences = vec![(0, 1)];
+    for walk in walks {
+        congruences = congruences
+            .into_iter()
+            .flat_map(|congruence| {
+                walk.cycle_hits
+                    .iter()
+                    .filter_map(move |&hit| crt(congruence, (hit as i128, walk.length as i128)))
+            })
+            .collect();
+        congruences.sort_unstable();
+        congruences.dedup();
+    }
+
+    let cycle_hit = congruences
+        .into_iter()
+        .map(|(rest, modulo)| {
+            let start = start as i128;
+            (start + (rest - start).rem_euclid(modulo)) as usize
+        })
+        .min();
+
+    tail_hit
+        .into_iter()
+        .chain(cycle_hit)
+        .min()
+        .ok_or(NetworkErr::NoCommonStep)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::map::{parse_input, Network, NetworkErr};
+
+    use super::{crt, first_common_hit, Walk};
+
+    fn walks(network: &Network, pattern: &crate::map::Pattern) -> Vec<Walk> {
+        let jumps = network.jump_table(pattern);
+        network
+            .find(|name| name.ends_with('A'))
+            .into_iter()
+            .map(|start| {
+                Walk::new(network, pattern, &jumps, start, |idx| {
+                    network.name(idx).ends_with('Z')
+                })
+            })
+            .collect()
+    }
+
+    #[test]
+    fn generalized_crt() {
+        assert_eq!(crt((2, 3), (3, 5)), Some((8, 15)));
+        assert_eq!(crt((1, 4), (3, 6)), Some((9, 12)));
+        assert_eq!(crt((0, 4), (1, 6)), None);
+    }
+
+    #[test]
+    fn walk_cycles() {
+        let (network, pattern) = parse_input(
+            r#"LR
+
+11A = (11B, XXX)
+11B = (XXX, 11Z)
+11Z = (11B, XXX)
+22A = (22B, XXX)
+22B = (22C, 22C)
+22C = (22Z, 22Z)
+22Z = (22B, 22B)
+XXX = (XXX, XXX)"#,
+        )
+        .unwrap();
+
+        let walks = walks(&network, &pattern);
+        assert_eq!(
+            walks[0],
+            Walk {
+                tail: 1,
+                length: 2,
+                tail_hits: vec![],
+                cycle_hits: vec![2],
+            }
+        );
+
+        assert_eq!((walks[1].tail, walks[1].length), (1, 6));
+        assert_eq!(walks[1].cycle_hits, vec![3, 6]);
+
+        assert_eq!(first_common_hit(&walks), Ok(6));
+    }
+
+    #[test]
+    fn no_common_hit() {
+        let (network, pattern) = parse_input(
+            r#"L
+
+1A = (1Z, 1Z)
+1Z = (1A, 1A)
+2A = (2B, 2B)
+2B = (2A, 2A)"#,
+        )
+        .unwrap();
+
+        let walks = walks(&network, &pattern);
+        assert_eq!(first_common_hit(&walks), Err(NetworkErr::NoCommonStep));
+    }
+
+    #[test]
+    fn common_hit_on_tail() {
+        let (network, pattern) = parse_input(
+            r#"L
+
+1A = (1Z, 1Z)
+1Z = (1B, 1B)
+1B = (1B, 1B)
+2A = (2Z, 2Z)
+2Z = (2Z, 2Z)"#,
+        )
+        .unwrap();
+
+        let walks = walks(&network, &pattern);
+        assert_eq!(walks[0].tail_hits, vec![1]);
+        assert_eq!(first_common_hit(&walks), Ok(1));
+    }
+}
@@ -1,67 +1,43 @@
-use map::parse_input;
-
-use crate::map::Move;
+use ghost::Walk;
+use map::{parse_input, NetworkErr};
 
+mod ghost;
 mod map;
 
-fn part_1(input: &str) -> Result<usize, String> {
-    let mut steps = 0;
-
-    let (network, mut pattern) = parse_input(input)?;
-
-    let mut current = network
-        .get("AAA")
-        .ok_or("network is missing node AAA".to_string())?;
-    while current.name != "ZZZ" {
-        let next = match pattern.next().unwrap() {
-            Move::Left => current.left.as_str(),
-            Move::Right => current.right.as_str(),
-        };
-
-        current = network
-            .get(next)
-            .ok_or_else(|| format!("the network is missing node: {}", next))?;
-
-        steps += 1;
-    }
-
-    Ok(steps)
+fn part_1(input: &str) -> Result<usize, NetworkErr> {
+    let (network, pattern) = parse_input(input)?;
+    let (start, end) = (network.index_of("AAA")?, network.index_of("ZZZ")?);
+    tracing::trace!("network:\n{}", network.to_dot());
+
+    let jumps = network.jump_table(&pattern);
+    Walk::new(&network, &pattern, &jumps, start, |idx| idx == end)
+        .first_hit()
+        .ok_or_else(|| NetworkErr::Unreachable {
+            from: "AAA".into(),
+            to: "ZZZ".into(),
+        })
 }
 
-fn part_2(input: &str) -> Result<usize, String> {
+fn part_2(input: &str) -> Result<usize, NetworkErr> {
     let (network, pattern) = parse_input(input)?;
+    let targets = network.find(|name| name.ends_with('Z'));
+    let jumps = network.jump_table(&pattern);
 
-    let nodes = network
-        .values()
-        .filter(|node| node.name.ends_with('A'))
-        .collect::<Vec<_>>();
-
-    let cycles = nodes
+    let walks = network
+        .find(|name| name.ends_with('A'))
         .into_iter()
-        .map(|mut current| {
-            let mut steps = 0usize;
-            let mut pattern = pattern.clone();
-            while !current.name.ends_with('Z') {
-                let next = match pattern.next().unwrap() {
-                    Move::Left => current.left.as_str(),
-                    Move::Right => current.right.as_str(),
-                };
-
-                current = network
-                    .get(next)
-                    .ok_or_else(|| format!("the network is missing node: {}", next))?;
-
-                steps += 1;
-            }
-
-            Ok::<_, String>(steps)
+        .map(|start| {
+            Walk::new(&network, &pattern, &jumps, start, |idx| {
+                targets.contains(&idx)
+            })
         })
-        .collect::<Result<Vec<_>, _>>()?;
+        .collect::<Vec<_>>();
 
-    Ok(cycles.into_iter().fold(1, num::integer::lcm))
+    ghost::first_common_hit(&walks)
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
+    tracing_subscriber::fmt::init();
     let input = rust_shared_utils::read_puzzle()?;
     println!("part_1 = {}", part_1(&input)?);
     println!("part_2 = {}", part_2(&input)?);
@@ -1,5 +1,32 @@
 use std::{collections::HashMap, str::FromStr};
 
+#[derive(thiserror::Error, Debug, PartialEq, Eq)]
+pub enum NetworkErr {
+    #[error("unknown input format")]
+    UnknownFormat,
+
+    #[error("unknown move: {0}")]
+    UnknownMove(char),
+
+    #[error("the pattern can not be empty")]
+    EmptyPattern,
+
+    #[error("unknown node format: {0}")]
+    BadNode(String),
+
+    #[error("the network is missing node: {0}")]
+    MissingNode(String),
+
+    #[error("node {0} appears more than once in the network")]
+    DuplicateNode(String),
+
+    #[error("node {to} can't be reached from {from}")]
+    Unreachable { from: String, to: String },
+
+    #[error("there is no step where every ghost is on a target node")]
+    NoCommonStep,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Move {
     Right,
@@ -16,6 +43,10 @@ impl Pattern {
     fn new(moves: Vec<Move>) -> Self {
         Self { moves, current: 0 }
     }
+
+    pub fn moves(&self) -> &[Move] {
+        &self.moves
+    }
 }
 
 impl Iterator for Pattern {
@@ -30,7 +61,8 @@ impl Iterator for Pattern {
 }
 
 impl FromStr for Pattern {
-    type Err = String;
+    type Err = NetworkErr;
+
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let moves = s
             .trim()
@@ -38,89 +70,193 @@ impl FromStr for Pattern {
             .map(|ch| match ch {
                 'R' => Ok(Move::Right),
                 'L' => Ok(Move::Left),
-                _ => Err(format!("unknown move {}", ch)),
+                _ => Err(NetworkErr::UnknownMove(ch)),
             })
             .collect::<Result<Vec<_>, _>>()?;
 
         if moves.is_empty() {
-            return Err("empty pattern".into());
+            return Err(NetworkErr::EmptyPattern);
         }
 
         Ok(Self::new(moves))
     }
 }
 
+/// The nodes of the network, interned into indices in the order they appear
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub struct Node {
-    pub name: String,
-    pub left: String,
-    pub right: String,
+pub struct Network {
+    names: Vec<String>,
+    indices: HashMap<String, usize>,
+    left: Vec<usize>,
+    right: Vec<usize>,
 }
 
-pub fn parse_network(raw: &str) -> Result<HashMap<String, Node>, &'static str> {
-    raw.trim()
-        .lines()
-        .map(|line| {
-            let (name, dest) = line.split_once('=')?;
-            let dest = dest.trim().strip_prefix('(').unwrap_or(dest);
-            let dest = dest.strip_suffix(')').unwrap_or(dest);
-            let (left, right) = dest.split_once(',')?;
-            let node = Node {
-                name: name.trim().to_string(),
-                left: left.trim().to_string(),
-                right: right.trim().to_string(),
+impl Network {
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn index_of(&self, name: &str) -> Result<usize, NetworkErr> {
+        self.indices
+            .get(name)
+            .copied()
+            .ok_or_else(|| NetworkErr::MissingNode(name.into()))
+    }
+
+    pub fn name(&self, idx: usize) -> &str {
+        &self.names[idx]
+    }
+
+    /// The indices of every node whose name matches
+    pub fn find(&self, matches: impl Fn(&str) -> bool) -> Vec<usize> {
+        (0..self.len())
+            .filter(|&idx| matches(self.name(idx)))
+            .collect()
+    }
+
+    pub fn next(&self, idx: usize, step: Move) -> usize {
+        match step {
+            Move::Left => self.left[idx],
+            Move::Right => self.right[idx],
+        }
+    }
+
+    /// Where every node ends up after following the whole pattern once
+    pub fn jump_table(&self, pattern: &Pattern) -> Vec<usize> {
+        (0..self.len())
+            .map(|idx| {
+                pattern
+                    .moves()
+                    .iter()
+                    .fold(idx, |idx, &step| self.next(idx, step))
+            })
+            .collect()
+    }
+
+    /// The node reached after `steps` steps from `start`, skipping
+    /// over full passes of the pattern with the jump table
+    pub fn position_after(
+        &self,
+        pattern: &Pattern,
+        jumps: &[usize],
+        start: usize,
+        steps: usize,
+    ) -> usize {
+        let moves = pattern.moves();
+        let idx = (0..steps / moves.len()).fold(start, |idx, _| jumps[idx]);
+        moves[..steps % moves.len()]
+            .iter()
+            .fold(idx, |idx, &step| self.next(idx, step))
+    }
+
+    /// Renders the network in the graphviz format, with an edge per move
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph network {\n");
+        for idx in 0..self.len() {
+            let (left, right) = (self.left[idx], self.right[idx]);
+            let edges = match left == right {
+                true => vec![(left, "LR")],
+                false => vec![(left, "L"), (right, "R")],
             };
 
-            Some((node.name.clone(), node))
-        })
-        .collect::<Option<HashMap<_, _>>>()
-        .ok_or("unknown network format")
+            for (next, label) in edges {
+                dot.push_str(&format!(
+                    "    \"{}\" -> \"{}\" [label=\"{}\"];\n",
+                    self.name(idx),
+                    self.name(next),
+                    label
+                ));
+            }
+        }
+        dot.push_str("}\n");
+
+        dot
+    }
 }
 
-pub fn parse_input(input: &str) -> Result<(HashMap<String, Node>, Pattern), String> {
-    let (pattern, network) = input
-        .split_once("\n\n")
-        .ok_or("unknown input format".to_string())?;
+impl FromStr for Network {
+    type Err = NetworkErr;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let nodes = s
+            .trim()
+            .lines()
+            .map(|line| {
+                let bad_node = || NetworkErr::BadNode(line.into());
+                let (name, dest) = line.split_once('=').ok_or_else(bad_node)?;
+                let dest = dest.trim().strip_prefix('(').unwrap_or(dest);
+                let dest = dest.strip_suffix(')').unwrap_or(dest);
+                let (left, right) = dest.split_once(',').ok_or_else(bad_node)?;
+
+                Ok((name.trim(), left.trim(), right.trim()))
+            })
+            .collect::<Result<Vec<_>, NetworkErr>>()?;
+
+        let mut indices = HashMap::new();
+        for (idx, (name, _, _)) in nodes.iter().enumerate() {
+            if indices.insert(name.to_string(), idx).is_some() {
+                return Err(NetworkErr::DuplicateNode(name.to_string()));
+            }
+        }
+
+        let resolve = |name: &str| {
+            indices
+                .get(name)
+                .copied()
+                .ok_or_else(|| NetworkErr::MissingNode(name.into()))
+        };
+        let left = nodes
+            .iter()
+            .map(|(_, left, _)| resolve(left))
+            .collect::<Result<Vec<_>, _>>()?;
+        let right = nodes
+            .iter()
+            .map(|(_, _, right)| resolve(right))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self {
+            names: nodes.iter().map(|(name, _, _)| name.to_string()).collect(),
+            indices,
+            left,
+            right,
+        })
+    }
+}
 
-    let pattern: Pattern = pattern.parse()?;
-    let network = parse_network(network)?;
+pub fn parse_input(input: &str) -> Result<(Network, Pattern), NetworkErr> {
+    let (pattern, network) = input.split_once("\n\n").ok_or(NetworkErr::UnknownFormat)?;
 
-    Ok((network, pattern))
+    Ok((network.parse()?, pattern.parse()?))
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::map::Node;
+    use super::{parse_input, Move, Network, NetworkErr, Pattern};
 
-    use super::{Move, Pattern};
+    const INPUT: &str = r#"LLR
 
-    #[test]
-    fn parse_network() {
-        let input = r#"AAA = (BBB, BBB)
+AAA = (BBB, BBB)
 BBB = (AAA, ZZZ)
 ZZZ = (ZZZ, ZZZ)"#;
-        let expected_output = [
-            Node {
-                name: "AAA".into(),
-                left: "BBB".into(),
-                right: "BBB".into(),
-            },
-            Node {
-                name: "BBB".into(),
-                left: "AAA".into(),
-                right: "ZZZ".into(),
-            },
-            Node {
-                name: "ZZZ".into(),
-                left: "ZZZ".into(),
-                right: "ZZZ".into(),
-            },
-        ];
-        let output = super::parse_network(input).unwrap();
-
-        for node in expected_output {
-            assert_eq!(output[&node.name], node);
-        }
+
+    #[test]
+    fn parse_network() {
+        let (network, _) = parse_input(INPUT).unwrap();
+
+        let [aaa, bbb, zzz] = ["AAA", "BBB", "ZZZ"].map(|name| network.index_of(name).unwrap());
+        assert_eq!(network.next(aaa, Move::Left), bbb);
+        assert_eq!(network.next(bbb, Move::Left), aaa);
+        assert_eq!(network.next(bbb, Move::Right), zzz);
+        assert_eq!(network.name(zzz), "ZZZ");
+
+        assert_eq!(
+            "AAA = (BBB, CCC)\nBBB = (AAA, AAA)".parse::<Network>(),
+            Err(NetworkErr::MissingNode("CCC".into()))
+        );
+        assert_eq!(
+            "AAA = BBB".parse::<Network>(),
+            Err(NetworkErr::BadNode("AAA = BBB".into()))
+        );
     }
 
     #[test]
@@ -130,5 +266,33 @@ ZZZ = (ZZZ, ZZZ)"#;
 
         let output: Pattern = input.parse().unwrap();
         assert_eq!(output, expected_output);
+        assert_eq!("LXR".parse::<Pattern>(), Err(NetworkErr::UnknownMove('X')));
+    }
+
+    #[test]
+    fn jump_table() {
+        let (network, pattern) = parse_input(INPUT).unwrap();
+        let jumps = network.jump_table(&pattern);
+
+        let [aaa, bbb, zzz] = ["AAA", "BBB", "ZZZ"].map(|name| network.index_of(name).unwrap());
+        assert_eq!(jumps, vec![bbb, zzz, zzz]);
+        assert_eq!(network.position_after(&pattern, &jumps, aaa, 3), bbb);
+        assert_eq!(network.position_after(&pattern, &jumps, aaa, 5), bbb);
+        assert_eq!(network.position_after(&pattern, &jumps, aaa, 6), zzz);
+    }
+
+    #[test]
+    fn to_dot() {
+        let (network, _) = parse_input(INPUT).unwrap();
+        assert_eq!(
+            network.to_dot(),
+            r#"digraph network {
+    "AAA" -> "BBB" [label="LR"];
+    "BBB" -> "AAA" [label="L"];
+    "BBB" -> "ZZZ" [label="R"];
+    "ZZZ" -> "ZZZ" [label="LR"];
+}
+"#
+        );
     }
 }
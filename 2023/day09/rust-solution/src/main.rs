@@ -1,8 +1,9 @@
 use oasis::{History, OasisErr};
 
 mod oasis;
+mod polynomial;
 
-fn part_1(input: &str) -> Result<i32, OasisErr> {
+fn part_1(input: &str) -> Result<i128, OasisErr> {
     input
         .trim()
         .lines()
@@ -11,10 +12,10 @@ fn part_1(input: &str) -> Result<i32, OasisErr> {
                 .parse::<History>()
                 .and_then(|history| history.predict_next())
         })
-        .sum::<Result<i32, _>>()
+        .sum::<Result<i128, _>>()
 }
 
-fn part_2(input: &str) -> Result<i32, OasisErr> {
+fn part_2(input: &str) -> Result<i128, OasisErr> {
     input
         .trim()
         .lines()
@@ -23,7 +24,7 @@ fn part_2(input: &str) -> Result<i32, OasisErr> {
                 .parse::<History>()
                 .and_then(|history| history.predict_prev())
         })
-        .sum::<Result<i32, _>>()
+        .sum::<Result<i128, _>>()
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -1,5 +1,7 @@
 use std::str::FromStr;
 
+use crate::polynomial::Polynomial;
+
 #[derive(thiserror::Error, Debug)]
 pub enum OasisErr {
     #[error("can not recognize the history format")]
@@ -10,6 +12,12 @@ pub enum OasisErr {
 
     #[error("can not reach the zero sequence")]
     EmptyDifference,
+
+    #[error("the prediction at offset {0} does not fit in i128")]
+    Overflow(i64),
+
+    #[error("the differences of the history do not fit in i128")]
+    DifferenceOverflow,
 }
 
 #[derive(Debug, PartialEq, Eq, Clone)]
@@ -17,55 +25,66 @@ pub struct History {
     values: Vec<i32>,
 }
 
+/// The polynomial going through every value of the history
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Fit {
+    pub polynomial: Polynomial,
+    /// whether the differences reached a sequence of zeros,
+    /// otherwise the polynomial just goes through every value
+    pub well_behaved: bool,
+}
+
 impl History {
     /// Predict the next value in the history
-    pub fn predict_next(&self) -> Result<i32, OasisErr> {
-        let differences = self.calculate_differences()?;
-
-        let mut value = 0;
-        for dif in differences.iter().rev() {
-            value += dif.last().unwrap();
-        }
-
-        Ok(value)
+    pub fn predict_next(&self) -> Result<i128, OasisErr> {
+        self.predict(self.values.len() as i64)
     }
 
     /// Predict the previous value in the history
-    pub fn predict_prev(&self) -> Result<i32, OasisErr> {
-        let differences = self.calculate_differences()?;
+    pub fn predict_prev(&self) -> Result<i128, OasisErr> {
+        self.predict(-1)
+    }
 
-        let mut value = 0;
-        for dif in differences.iter().rev() {
-            value = dif.first().unwrap() - value;
+    /// Predict the value at offset `k` from the first one
+    pub fn predict(&self, k: i64) -> Result<i128, OasisErr> {
+        let fit = self.fit()?;
+        if !fit.well_behaved {
+            return Err(OasisErr::EmptyDifference);
         }
 
-        Ok(value)
+        fit.polynomial.value_at(k).ok_or(OasisErr::Overflow(k))
     }
 
-    fn calculate_differences(&self) -> Result<Vec<Vec<i32>>, OasisErr> {
-        let mut differences = Vec::new();
-        differences.push(self.values.clone());
-
-        loop {
-            let dif = differences
-                .last()
-                .unwrap()
-                .windows(2)
-                .map(|values| values[1] - values[0])
-                .collect::<Vec<_>>();
-
-            if dif.is_empty() {
-                return Err(OasisErr::EmptyDifference);
+    /// Builds the difference table, keeping only the first value of every row
+    pub fn fit(&self) -> Result<Fit, OasisErr> {
+        let mut row = self
+            .values
+            .iter()
+            .map(|&value| value as i128)
+            .collect::<Vec<_>>();
+
+        let mut differences = vec![];
+        let well_behaved = loop {
+            if row.iter().all(|value| *value == 0) {
+                break true;
             }
 
-            // stop when we reach the last sequence
-            if dif.iter().all(|value| *value == 0) {
-                break;
+            differences.push(row[0]);
+            if row.len() == 1 {
+                break false;
             }
-            differences.push(dif);
-        }
 
-        Ok(differences)
+            row = row
+                .windows(2)
+                .map(|values| values[1].checked_sub(values[0]))
+                .collect::<Option<_>>()
+                .ok_or(OasisErr::DifferenceOverflow)?;
+        };
+
+        Ok(Fit {
+            polynomial: Polynomial::new(differences),
+            well_behaved,
+        })
     }
 }
 
@@ -90,7 +109,7 @@ impl FromStr for History {
 
 #[cfg(test)]
 mod tests {
-    use super::History;
+    use super::{History, OasisErr};
 
     #[test]
     fn parse_history() {
@@ -123,4 +142,49 @@ mod tests {
         let output = input.predict_prev().unwrap();
         assert_eq!(output, expected_output);
     }
+
+    #[test]
+    fn fit_polynomial() {
+        let input: History = "10 13 16 21 30 45".parse().unwrap();
+        let fit = input.fit().unwrap();
+
+        assert!(fit.well_behaved);
+        assert_eq!(fit.polynomial.degree(), 3);
+        assert_eq!(fit.polynomial.value_at(6), Some(68));
+        assert_eq!(fit.polynomial.value_at(-1), Some(5));
+        assert_eq!(input.predict(1_000_000).unwrap(), 333_332_333_337_000_010);
+
+        let input: History = "1 2 4".parse().unwrap();
+        let fit = input.fit().unwrap();
+        assert!(!fit.well_behaved);
+        assert_eq!(fit.polynomial.degree(), 2);
+        assert_eq!(fit.polynomial.value_at(3), Some(7));
+        assert!(input.predict_next().is_err());
+    }
+
+    #[test]
+    fn predict_overflow() {
+        // C(k, 20)
+        let input: History = "0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 1 21"
+            .parse()
+            .unwrap();
+
+        assert_eq!(input.predict_next().unwrap(), 231);
+        assert!(matches!(
+            input.predict(1_000_000),
+            Err(OasisErr::Overflow(1_000_000))
+        ));
+
+        // alternating extremes double the differences on every row
+        let input: History = [i32::MAX, i32::MIN]
+            .iter()
+            .cycle()
+            .take(120)
+            .map(|value| value.to_string())
+            .collect::<Vec<_>>()
+            .join(" ")
+            .parse()
+            .unwrap();
+        assert!(matches!(input.fit(), Err(OasisErr::DifferenceOverflow)));
+    }
 }
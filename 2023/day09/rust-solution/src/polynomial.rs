@@ -0,0 +1,158 @@
+use std::fmt::{self, Display};
+
+use num::{rational::Ratio, BigInt, Zero};
+
+/// A polynomial in Newton's forward form, a sum of `coefficient * k^(j)`
+/// where `k^(j) = k (k - 1) ... (k - j + 1)` is the falling factorial
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Polynomial {
+    /// the first value of every row of the difference table,
+    /// `value_at` works with them to stay in integers
+    differences: Vec<i128>,
+}
+
+impl Polynomial {
+    pub fn new(differences: Vec<i128>) -> Self {
+        Self { differences }
+    }
+
+    /// The coefficient of `k^(j)` is the j-th difference divided by `j!`,
+    /// the factorials outgrow i128 past a few dozen rows
+    pub fn coefficients(&self) -> Vec<Ratio<BigInt>> {
+        let mut factorial = BigInt::from(1);
+        self.differences
+            .iter()
+            .enumerate()
+            .map(|(idx, &difference)| {
+                factorial *= idx.max(1);
+                Ratio::new(BigInt::from(difference), factorial.clone())
+            })
+            .collect()
+    }
+
+    pub fn degree(&self) -> usize {
+        self.differences
+            .iter()
+            .rposition(|difference| !difference.is_zero())
+            .unwrap_or(0)
+    }
+
+    /// The value at offset `k` from the first value of the history, `k` may be negative,
+    /// `None` if the value or any of the binomial coefficients on the way don't fit in i128
+    pub fn value_at(&self, k: i64) -> Option<i128> {
+        let k = k as i128;
+
+        // the binomial coefficient `C(k, j)` for every `j`, built one from the other,
+        // the division is exact since `C(k, j - 1) (k - j + 1) = C(k, j) j`
+        let mut binomial: i128 = 1;
+        let mut value: i128 = 0;
+        // the zero differences past the degree would only overflow the binomials
+        let terms = self.differences.iter().take(self.degree() + 1);
+        for (idx, &difference) in terms.enumerate().map(|(idx, d)| (idx as i128, d)) {
+            if idx > 0 {
+                binomial = binomial.checked_mul(k - idx + 1)? / idx;
+            }
+            value = value.checked_add(difference.checked_mul(binomial)?)?;
+        }
+
+        Some(value)
+    }
+}
+
+/// Displays the polynomial in the falling factorial basis, as `3/2 k^(2) + 1`
+impl Display for Polynomial {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let terms = self
+            .coefficients()
+            .into_iter()
+            .enumerate()
+            .rev()
+            .filter(|(_, coefficient)| !coefficient.is_zero())
+            .map(|(idx, coefficient)| match idx {
+                0 => coefficient.to_string(),
+                _ => format!("{} k^({})", coefficient, idx),
+            })
+            .collect::<Vec<_>>();
+
+        match terms.is_empty() {
+            true => write!(f, "0"),
+            false => write!(f, "{}", terms.join(" + ")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num::{rational::Ratio, BigInt};
+
+    use super::Polynomial;
+
+    #[test]
+    fn triangular_numbers() {
+        // 1 3 6 10 15 21
+        let polynomial = Polynomial::new(vec![1, 2, 1]);
+
+        assert_eq!(polynomial.degree(), 2);
+        assert_eq!(
+            polynomial.coefficients(),
+            vec![
+                Ratio::from(BigInt::from(1)),
+                Ratio::from(BigInt::from(2)),
+                Ratio::new(BigInt::from(1), BigInt::from(2))
+            ]
+        );
+        assert_eq!(polynomial.to_string(), "1/2 k^(2) + 2 k^(1) + 1");
+
+        let values = (-2..7)
+            .map(|k| polynomial.value_at(k).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(values, vec![0, 0, 1, 3, 6, 10, 15, 21, 28]);
+    }
+
+    #[test]
+    fn zero_polynomial() {
+        let polynomial = Polynomial::new(vec![]);
+
+        assert_eq!(polynomial.degree(), 0);
+        assert_eq!(polynomial.value_at(-100), Some(0));
+        assert_eq!(polynomial.to_string(), "0");
+    }
+
+    #[test]
+    fn many_rows() {
+        // 40 rows of differences, 39! doesn't fit in i128
+        let mut differences = vec![0; 39];
+        differences.push(1);
+        let polynomial = Polynomial::new(differences);
+
+        let coefficients = polynomial.coefficients();
+        assert_eq!(
+            coefficients[39],
+            Ratio::new(
+                BigInt::from(1),
+                (1..=39).map(BigInt::from).product::<BigInt>()
+            )
+        );
+        assert!(polynomial.to_string().ends_with(" k^(39)"));
+    }
+
+    #[test]
+    fn large_offsets() {
+        // C(k, 20), as fitted from a 21 values history
+        let mut differences = vec![0; 20];
+        differences.push(1);
+        let polynomial = Polynomial::new(differences);
+
+        assert_eq!(polynomial.value_at(21), Some(21));
+        assert_eq!(polynomial.value_at(1_000_000), None);
+
+        // trailing zero differences don't take part in the sum
+        let mut differences = vec![1, 2, 1];
+        differences.extend([0; 18]);
+        let polynomial = Polynomial::new(differences);
+        assert_eq!(
+            polynomial.value_at(1_000_000_000_000),
+            Some(500_000_000_001_500_000_000_001)
+        );
+    }
+}
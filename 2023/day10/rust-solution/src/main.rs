@@ -1,148 +1,34 @@
-use std::collections::HashSet;
-
-use sketch::{Pipe, Point, SketchErr};
-
-use crate::sketch::{Sketch, Tile};
+use sketch::{Sketch, SketchErr};
 
 mod sketch;
 
 fn part_1(input: &str) -> Result<Option<usize>, SketchErr> {
     let sketch: Sketch = input.parse()?;
-    let start = sketch.start().ok_or(SketchErr::NoStart)?;
-
-    // Get all points coming out of the start
-    let mut frontier = start
-        .all_dirs()
-        .into_iter()
-        .flatten()
-        .map(|position| (1, start, position))
-        .collect::<Vec<_>>();
-    let mut visited = HashSet::from([start]);
-
-    while !frontier.is_empty() {
-        let mut new = vec![];
-        while let Some((steps, prev, current)) = frontier.pop() {
-            let pipe = match sketch.tile(current) {
-                Some(Tile::Pipe(pipe)) => pipe,
-                _ => continue, // ignore non-pipes
-            };
-
-            let ends = pipe.ends(current);
-
-            if !ends.contains(&Some(prev)) {
-                // never go to a pipe that doesn't go back to you
-                continue;
-            }
-
-            if !visited.insert(current) {
-                return Ok(Some(steps));
-            }
-
-            let next = match pipe.ends(current).iter().find(|&&pos| pos != Some(prev)) {
-                Some(Some(next)) => *next,
-                _ => continue,
-            };
-
-            new.push((steps + 1, current, next));
-        }
-
-        frontier = new;
+    match sketch.main_loop() {
+        Ok(main_loop) => Ok(Some(main_loop.points.len() / 2)),
+        Err(SketchErr::NoLoop) => Ok(None),
+        Err(err) => Err(err),
     }
-
-    Ok(None)
 }
 
 fn part_2(input: &str) -> Result<Option<usize>, SketchErr> {
     let sketch: Sketch = input.parse()?;
-    let Some(lp) = get_loop(&sketch)? else {
-        return Ok(None);
+    let main_loop = match sketch.main_loop() {
+        Ok(main_loop) => main_loop,
+        Err(SketchErr::NoLoop) => return Ok(None),
+        Err(err) => return Err(err),
     };
 
-    let start_goes_north = sketch
-        .start()
-        .unwrap()
-        .north()
-        .map(|position| {
-            lp.contains(&position)
-                && matches!(
-                    sketch.tile(position),
-                    Some(Tile::Pipe(
-                        Pipe::SouthEast | Pipe::SouthWest | Pipe::Vertical
-                    ))
-                )
-        })
-        .unwrap_or(false);
-
-    let enclosed = sketch
-        .tiles
-        .iter()
-        .enumerate()
-        .map(|(rdx, row)| {
-            let mut count = 0;
-
-            row.iter()
-                .enumerate()
-                .filter(|(cdx, col)| {
-                    if lp.contains(&Point::new(*cdx, rdx)) {
-                        if matches!(
-                            col,
-                            Tile::Pipe(Pipe::NorthEast | Pipe::NorthWest | Pipe::Vertical)
-                        ) || (start_goes_north && matches!(col, Tile::Start))
-                        {
-                            count += 1;
-                        }
+    tracing::debug!("main loop:\n{}", sketch.render(&main_loop));
 
-                        false
-                    } else {
-                        count % 2 == 1
-                    }
-                })
-                .count()
-        })
-        .sum();
+    let enclosed = main_loop.enclosed_tiles();
+    debug_assert_eq!(enclosed, sketch.inside_tiles(&main_loop).len());
 
     Ok(Some(enclosed))
 }
 
-fn get_loop(sketch: &Sketch) -> Result<Option<HashSet<Point>>, SketchErr> {
-    let start = sketch.start().ok_or(SketchErr::NoStart)?;
-
-    // get all tiles that are connected back to the start
-    let connected_to_start = start.all_dirs().into_iter().flatten().collect::<Vec<_>>();
-
-    'start_loop: for point in connected_to_start {
-        let mut path = vec![start];
-        let mut prev = start;
-        let mut current = point;
-        while current != start {
-            let pipe = match sketch.tile(current) {
-                Some(Tile::Pipe(pipe)) => pipe,
-                _ => continue 'start_loop,
-            };
-
-            let ends = pipe.ends(current);
-            if !ends.contains(&Some(prev)) {
-                // never go to a pipe that doesn't go back to you
-                continue 'start_loop;
-            };
-
-            let next = match ends.into_iter().find(|&pos| pos != Some(prev)) {
-                Some(Some(next)) => next,
-                _ => continue 'start_loop,
-            };
-
-            path.push(current);
-            prev = current;
-            current = next;
-        }
-
-        return Ok(Some(path.into_iter().collect()));
-    }
-
-    Ok(None)
-}
-
 fn main() -> Result<(), Box<dyn std::error::Error>> {
+    tracing_subscriber::fmt::init();
     let input = rust_shared_utils::read_puzzle()?;
     println!("part 1: {:?}", part_1(&input)?);
     println!("part 2: {:?}", part_2(&input)?);
@@ -202,4 +88,10 @@ L.L7LFJ|||||FJL7||LJ
 L7JLJL-JLJLJL--JLJ.L"#;
         assert_eq!(super::part_2(input).unwrap().unwrap(), 10);
     }
+
+    #[test]
+    fn part_2_empty_loops() {
+        assert_eq!(super::part_2("S7\nLJ").unwrap().unwrap(), 0);
+        assert_eq!(super::part_2("S--7\nL--J").unwrap().unwrap(), 0);
+    }
 }
@@ -1,4 +1,4 @@
-use std::str::FromStr;
+use std::{collections::HashSet, str::FromStr};
 
 #[derive(thiserror::Error, Debug)]
 pub enum SketchErr {
@@ -7,6 +7,9 @@ pub enum SketchErr {
 
     #[error("the sketch is missing the start position")]
     NoStart,
+
+    #[error("the start isn't part of any loop")]
+    NoLoop,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -20,6 +23,38 @@ pub enum Pipe {
 }
 
 impl Pipe {
+    pub const ALL: [Self; 6] = [
+        Self::Vertical,
+        Self::Horizontal,
+        Self::NorthEast,
+        Self::NorthWest,
+        Self::SouthWest,
+        Self::SouthEast,
+    ];
+
+    /// The pipe at the position that connects both points
+    pub fn connecting(position: Point, a: Point, b: Point) -> Option<Self> {
+        Self::ALL.into_iter().find(|pipe| {
+            let ends = pipe.ends(position);
+            ends.contains(&Some(a)) && ends.contains(&Some(b))
+        })
+    }
+
+    pub fn goes_north(&self) -> bool {
+        matches!(self, Self::Vertical | Self::NorthEast | Self::NorthWest)
+    }
+
+    pub fn box_drawing(&self) -> char {
+        match self {
+            Self::Vertical => '│',
+            Self::Horizontal => '─',
+            Self::NorthEast => '└',
+            Self::NorthWest => '┘',
+            Self::SouthWest => '┐',
+            Self::SouthEast => '┌',
+        }
+    }
+
     /// Get the 2 ends of a pipe from its position
     pub fn ends(&self, position: Point) -> [Option<Point>; 2] {
         match self {
@@ -126,6 +161,128 @@ impl Sketch {
             .and_then(|row| row.get(position.x))
             .copied()
     }
+
+    /// Walks the loop going through the start, and works out
+    /// which pipe is hiding under it from the two pipes it connects.
+    pub fn main_loop(&self) -> Result<MainLoop, SketchErr> {
+        let start = self.start().ok_or(SketchErr::NoStart)?;
+
+        'start_loop: for first in start.all_dirs().into_iter().flatten() {
+            let mut points = vec![start];
+            let mut prev = start;
+            let mut current = first;
+            while current != start {
+                let pipe = match self.tile(current) {
+                    Some(Tile::Pipe(pipe)) => pipe,
+                    _ => continue 'start_loop,
+                };
+
+                let ends = pipe.ends(current);
+                if !ends.contains(&Some(prev)) {
+                    // never go to a pipe that doesn't go back to you
+                    continue 'start_loop;
+                };
+
+                let next = match ends.into_iter().find(|&pos| pos != Some(prev)) {
+                    Some(Some(next)) => next,
+                    _ => continue 'start_loop,
+                };
+
+                points.push(current);
+                prev = current;
+                current = next;
+            }
+
+            // a loop made of the start and a single pipe can't be connected
+            let Some(start_pipe) = Pipe::connecting(start, first, prev) else {
+                continue;
+            };
+
+            return Ok(MainLoop { points, start_pipe });
+        }
+
+        Err(SketchErr::NoLoop)
+    }
+
+    /// The tile, with the start replaced by the pipe under it
+    fn loop_tile(&self, main_loop: &MainLoop, position: Point) -> Option<Tile> {
+        match self.tile(position) {
+            Some(Tile::Start) => Some(Tile::Pipe(main_loop.start_pipe)),
+            tile => tile,
+        }
+    }
+
+    /// The tiles inside the loop, found by scanning every row and
+    /// counting how many times the loop was crossed so far
+    pub fn inside_tiles(&self, main_loop: &MainLoop) -> HashSet<Point> {
+        let on_loop = main_loop.points.iter().copied().collect::<HashSet<_>>();
+
+        let mut inside = HashSet::new();
+        for (rdx, row) in self.tiles.iter().enumerate() {
+            let mut crossings = 0;
+            for cdx in 0..row.len() {
+                let position = Point::new(cdx, rdx);
+                if on_loop.contains(&position) {
+                    if let Some(Tile::Pipe(pipe)) = self.loop_tile(main_loop, position) {
+                        crossings += pipe.goes_north() as usize;
+                    }
+                } else if crossings % 2 == 1 {
+                    inside.insert(position);
+                }
+            }
+        }
+
+        inside
+    }
+
+    /// Draws the loop with box drawing characters, the tiles inside it
+    /// as shaded blocks, and leaves out everything else
+    pub fn render(&self, main_loop: &MainLoop) -> String {
+        let on_loop = main_loop.points.iter().copied().collect::<HashSet<_>>();
+        let inside = self.inside_tiles(main_loop);
+
+        let mut out = String::new();
+        for (rdx, row) in self.tiles.iter().enumerate() {
+            for cdx in 0..row.len() {
+                let position = Point::new(cdx, rdx);
+                out.push(match self.loop_tile(main_loop, position) {
+                    Some(Tile::Pipe(pipe)) if on_loop.contains(&position) => pipe.box_drawing(),
+                    _ if inside.contains(&position) => '▒',
+                    _ => ' ',
+                });
+            }
+            out.push('\n');
+        }
+
+        out
+    }
+}
+
+/// The tiles of the loop in order, starting at the start
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MainLoop {
+    pub points: Vec<Point>,
+    /// the pipe the start is sitting on
+    pub start_pipe: Pipe,
+}
+
+impl MainLoop {
+    /// Counts the tiles inside the loop using the shoelace formula
+    /// for its area, and Pick's theorem to remove the loop itself
+    pub fn enclosed_tiles(&self) -> usize {
+        let points = &self.points;
+
+        let double_area = points
+            .iter()
+            .zip(points.iter().cycle().skip(1))
+            .map(|(a, b)| a.x as isize * b.y as isize - b.x as isize * a.y as isize)
+            .sum::<isize>()
+            .unsigned_abs();
+
+        // a loop only two tiles wide has no area left for Pick's theorem to remove
+        // the loop from, adding before subtracting keeps the count at zero
+        (double_area + 2 - points.len()) / 2
+    }
 }
 
 impl FromStr for Sketch {
@@ -160,7 +317,7 @@ impl FromStr for Sketch {
 
 #[cfg(test)]
 mod tests {
-    use super::{Pipe, Point, Sketch, Tile};
+    use super::{Pipe, Point, Sketch, SketchErr, Tile};
 
     #[test]
     fn parse_sketch() {
@@ -241,4 +398,72 @@ mod tests {
         let expected_ends = [Some(Point { x: 5, y: 7 }), Some(Point { x: 6, y: 8 })];
         assert_eq!(ends, expected_ends);
     }
+
+    #[test]
+    fn main_loop() {
+        let input: Sketch = r#"..F7.
+.FJ|.
+SJ.L7
+|F--J
+LJ..."#
+            .parse()
+            .unwrap();
+
+        let main_loop = input.main_loop().unwrap();
+        assert_eq!(main_loop.start_pipe, Pipe::SouthEast);
+        assert_eq!(main_loop.points.len(), 16);
+        assert_eq!(main_loop.points[0], Point { x: 0, y: 2 });
+
+        let input: Sketch = "S-7\n|.|\nL-.".parse().unwrap();
+        assert!(matches!(input.main_loop(), Err(SketchErr::NoLoop)));
+    }
+
+    #[test]
+    fn enclosed_tiles() {
+        let input: Sketch = r#".F----7F7F7F7F-7....
+.|F--7||||||||FJ....
+.||.FJ||||||||L7....
+FJL7L7LJLJ||LJ.L-7..
+L--J.L7...LJS7F-7L7.
+....F-J..F7FJ|L7L7L7
+....L7.F7||L7|.L7L7|
+.....|FJLJ|FJ|F7|.LJ
+....FJL-7.||.||||...
+....L---J.LJ.LJLJ..."#
+            .parse()
+            .unwrap();
+
+        let main_loop = input.main_loop().unwrap();
+        assert_eq!(main_loop.enclosed_tiles(), 8);
+        assert_eq!(input.inside_tiles(&main_loop).len(), 8);
+    }
+
+    #[test]
+    fn render_loop() {
+        let input: Sketch = r#"...........
+.S-------7.
+.|F-----7|.
+.||.....||.
+.||.....||.
+.|L-7.F-J|.
+.|..|.|..|.
+.L--J.L--J.
+..........."#
+            .parse()
+            .unwrap();
+
+        assert_eq!(
+            input.render(&input.main_loop().unwrap()),
+            r#"           
+ ┌───────┐ 
+ │┌─────┐│ 
+ ││     ││ 
+ ││     ││ 
+ │└─┐ ┌─┘│ 
+ │▒▒│ │▒▒│ 
+ └──┘ └──┘ 
+           
+"#
+        );
+    }
 }
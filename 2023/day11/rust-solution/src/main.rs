@@ -1,23 +1,43 @@
-use crate::universe::Universe;
+use crate::{
+    metric::{Chebyshev, Euclidean, Manhattan},
+    universe::{Image, UniverseErr},
+};
 
+mod metric;
 mod universe;
 
-fn part_1(input: &str) -> Option<usize> {
-    let universe = Universe::from_str(input, 2)?;
+fn part_1(input: &str) -> Result<usize, UniverseErr> {
+    let image: Image = input.parse()?;
+    let universe = image.expand(2, 2);
+
+    tracing::debug!(
+        "sum of distances with diagonal steps: {}, in straight lines: {:.2}",
+        universe.sum_of_distances(&Chebyshev),
+        universe.sum_of_distances(&Euclidean)
+    );
+    for idx in 0..universe.galaxies().len() {
+        tracing::trace!(
+            "galaxy {}: nearest {:?}, farthest {:?}",
+            idx,
+            universe.nearest(idx, &Manhattan),
+            universe.farthest(idx, &Manhattan)
+        );
+    }
 
-    Some(universe.minimum_path_sum())
+    Ok(universe.minimum_path_sum())
 }
 
-fn part_2(input: &str) -> Option<usize> {
-    let universe = Universe::from_str(input, 1000000)?;
+fn part_2(input: &str) -> Result<usize, UniverseErr> {
+    let image: Image = input.parse()?;
 
-    Some(universe.minimum_path_sum())
+    Ok(image.expand(1000000, 1000000).minimum_path_sum())
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
+    tracing_subscriber::fmt::init();
     let input = rust_shared_utils::read_puzzle()?;
-    println!("part 1: {}", part_1(&input).ok_or("empty graph")?);
-    println!("part 2: {}", part_2(&input).ok_or("empty graph")?);
+    println!("part 1: {}", part_1(&input)?);
+    println!("part 2: {}", part_2(&input)?);
 
     Ok(())
 }
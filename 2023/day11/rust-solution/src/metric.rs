@@ -0,0 +1,145 @@
+use std::iter::Sum;
+
+use crate::universe::Position;
+
+/// A way of measuring the distance between two galaxies
+pub trait Metric {
+    type Distance: Copy + Sum;
+
+    fn distance(&self, a: &Position, b: &Position) -> Self::Distance;
+
+    /// The sum of the distances between every pair of positions
+    fn sum_of_distances(&self, positions: &[Position]) -> Self::Distance {
+        positions
+            .iter()
+            .enumerate()
+            .flat_map(|(idx, a)| {
+                positions[idx + 1..]
+                    .iter()
+                    .map(move |b| self.distance(a, b))
+            })
+            .sum()
+    }
+}
+
+/// The sum of `|a - b|` over every pair of values, by sorting them:
+/// the value at index `i` is larger than the `i` values before it.
+fn sum_of_differences(mut values: Vec<i64>) -> u64 {
+    values.sort_unstable();
+
+    let mut prefix = 0;
+    let mut sum = 0;
+    for (idx, value) in values.into_iter().enumerate() {
+        sum += value * idx as i64 - prefix;
+        prefix += value;
+    }
+
+    sum as u64
+}
+
+/// The number of steps when moving up, down, left or right
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Manhattan;
+
+impl Metric for Manhattan {
+    type Distance = usize;
+
+    fn distance(&self, a: &Position, b: &Position) -> Self::Distance {
+        a.hamilton_distance(b)
+    }
+
+    fn sum_of_distances(&self, positions: &[Position]) -> Self::Distance {
+        let xs = positions.iter().map(|pos| pos.x as i64).collect();
+        let ys = positions.iter().map(|pos| pos.y as i64).collect();
+
+        (sum_of_differences(xs) + sum_of_differences(ys)) as usize
+    }
+}
+
+/// The number of steps when diagonal moves are allowed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Chebyshev;
+
+impl Metric for Chebyshev {
+    type Distance = usize;
+
+    fn distance(&self, a: &Position, b: &Position) -> Self::Distance {
+        a.x.abs_diff(b.x).max(a.y.abs_diff(b.y))
+    }
+
+    fn sum_of_distances(&self, positions: &[Position]) -> Self::Distance {
+        // rotating by 45 degrees turns it into half the manhattan distance,
+        // `max(|dx|, |dy|) = (|dx + dy| + |dx - dy|) / 2`
+        let us = positions.iter().map(|pos| (pos.x + pos.y) as i64).collect();
+        let vs = positions
+            .iter()
+            .map(|pos| pos.x as i64 - pos.y as i64)
+            .collect();
+
+        ((sum_of_differences(us) + sum_of_differences(vs)) / 2) as usize
+    }
+}
+
+/// The length of the straight line between the galaxies
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Euclidean;
+
+impl Metric for Euclidean {
+    type Distance = f64;
+
+    fn distance(&self, a: &Position, b: &Position) -> Self::Distance {
+        let dx = a.x.abs_diff(b.x) as f64;
+        let dy = a.y.abs_diff(b.y) as f64;
+        dx.hypot(dy)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::universe::Position;
+
+    use super::{Chebyshev, Euclidean, Manhattan, Metric};
+
+    fn pairwise<M: Metric>(metric: &M, positions: &[Position]) -> M::Distance {
+        positions
+            .iter()
+            .enumerate()
+            .flat_map(|(idx, a)| positions[idx + 1..].iter().map(|b| metric.distance(a, b)))
+            .sum()
+    }
+
+    #[test]
+    fn fast_sums_match_pairwise() {
+        let positions = [
+            (4, 0),
+            (9, 1),
+            (0, 2),
+            (8, 5),
+            (1, 6),
+            (12, 7),
+            (9, 10),
+            (0, 11),
+        ]
+        .map(|(x, y)| Position::new(x, y));
+
+        assert_eq!(
+            Manhattan.sum_of_distances(&positions),
+            pairwise(&Manhattan, &positions)
+        );
+        assert_eq!(
+            Chebyshev.sum_of_distances(&positions),
+            pairwise(&Chebyshev, &positions)
+        );
+    }
+
+    #[test]
+    fn euclidean() {
+        let positions = [
+            Position::new(0, 0),
+            Position::new(3, 4),
+            Position::new(0, 4),
+        ];
+        assert_eq!(Euclidean.distance(&positions[0], &positions[1]), 5.0);
+        assert_eq!(Euclidean.sum_of_distances(&positions), 12.0);
+    }
+}
@@ -1,8 +1,22 @@
-use std::collections::HashSet;
+use std::str::FromStr;
+
+use crate::metric::{Manhattan, Metric};
 
 const GALAXY: char = '#';
 const EMPTY: char = '.';
 
+#[derive(thiserror::Error, Debug)]
+pub enum UniverseErr {
+    #[error("the image can not be empty")]
+    Empty,
+
+    #[error("not all of the lines in the image are of the same length")]
+    NotEven,
+
+    #[error("unknown space in the image: {0}")]
+    UnknownSpace(char),
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct Position {
     pub x: usize,
@@ -10,7 +24,7 @@ pub struct Position {
 }
 
 impl Position {
-    fn new(x: usize, y: usize) -> Self {
+    pub fn new(x: usize, y: usize) -> Self {
         Self { x, y }
     }
 
@@ -19,92 +33,146 @@ impl Position {
     }
 }
 
+/// The image as observed, before accounting for the expansion of the universe
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub struct Universe {
+pub struct Image {
     galaxies: Vec<Position>,
+    /// sorted indices of the rows without galaxies
+    empty_rows: Vec<usize>,
+    /// sorted indices of the columns without galaxies
+    empty_cols: Vec<usize>,
 }
 
-impl Universe {
-    pub fn minimum_path_sum(&self) -> usize {
-        self.galaxies
+impl Image {
+    /// Replaces every empty row with `row_factor` rows, and every empty
+    /// column with `col_factor` columns, a factor of 1 changes nothing
+    pub fn expand(&self, row_factor: usize, col_factor: usize) -> Universe {
+        let grow = |idx: usize, empty: &[usize], factor: usize| {
+            let before = empty.partition_point(|&empty| empty < idx);
+            idx - before + before * factor
+        };
+
+        let galaxies = self
+            .galaxies
             .iter()
-            .enumerate()
-            .flat_map(|(i, g1)| {
-                self.galaxies
-                    .iter()
-                    .skip(i + 1)
-                    .map(|g2| g1.hamilton_distance(g2))
+            .map(|galaxy| {
+                Position::new(
+                    grow(galaxy.x, &self.empty_cols, col_factor),
+                    grow(galaxy.y, &self.empty_rows, row_factor),
+                )
             })
-            .sum::<usize>()
+            .collect();
+
+        Universe { galaxies }
     }
+}
+
+impl FromStr for Image {
+    type Err = UniverseErr;
 
-    pub fn from_str(s: &str, empty_column_factor: usize) -> Option<Self> {
-        let graph = s
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lines = s
+            .trim()
             .lines()
-            .map(|line| line.chars().collect::<Vec<_>>())
-            .collect::<Vec<Vec<_>>>();
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>();
 
-        if graph.is_empty() {
-            return None;
+        if lines.is_empty() {
+            return Err(UniverseErr::Empty);
         }
 
-        // find all empty rows/columns
-        #[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
-        enum Type {
-            Row,
-            Col,
+        let clen = lines[0].len();
+        if lines.iter().any(|line| line.len() != clen) {
+            return Err(UniverseErr::NotEven);
         }
-        let mut empty: HashSet<(Type, usize)> = Default::default();
-        // empty rows
-        empty.extend(
-            graph
-                .iter()
-                .enumerate()
-                .filter(|(_, row)| row.iter().all(|ch| *ch == EMPTY))
-                .map(|(rdx, _)| (Type::Row, rdx)),
-        );
-        // empty cols
-        empty.extend(
-            (0..graph[0].len())
-                .filter(|cdx| (0..graph.len()).all(|rdx| graph[rdx][*cdx] == EMPTY))
-                .map(|cdx| (Type::Col, cdx)),
-        );
 
         let mut galaxies = vec![];
-        let mut rdx_offset = 0;
-        for (rdx, row) in graph.iter().enumerate() {
-            if empty.contains(&(Type::Row, rdx)) {
-                rdx_offset += 1;
-                continue;
-            }
-
-            let mut cdx_offset = 0;
-            for (cdx, ch) in row.iter().enumerate() {
-                if empty.contains(&(Type::Col, cdx)) {
-                    cdx_offset += 1;
-                    continue;
-                }
-
-                if *ch == GALAXY {
-                    galaxies.push(Position::new(
-                        cdx + (cdx_offset * (empty_column_factor - 1)),
-                        rdx + (rdx_offset * (empty_column_factor - 1)),
-                    ));
+        for (rdx, line) in lines.iter().enumerate() {
+            for (cdx, ch) in line.chars().enumerate() {
+                match ch {
+                    GALAXY => galaxies.push(Position::new(cdx, rdx)),
+                    EMPTY => {}
+                    _ => return Err(UniverseErr::UnknownSpace(ch)),
                 }
             }
         }
 
-        Some(Self { galaxies })
+        let empty_rows = (0..lines.len())
+            .filter(|&rdx| galaxies.iter().all(|galaxy| galaxy.y != rdx))
+            .collect();
+        let empty_cols = (0..clen)
+            .filter(|&cdx| galaxies.iter().all(|galaxy| galaxy.x != cdx))
+            .collect();
+
+        Ok(Self {
+            galaxies,
+            empty_rows,
+            empty_cols,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Universe {
+    galaxies: Vec<Position>,
+}
+
+impl Universe {
+    pub fn galaxies(&self) -> &[Position] {
+        &self.galaxies
+    }
+
+    pub fn minimum_path_sum(&self) -> usize {
+        self.sum_of_distances(&Manhattan)
+    }
+
+    /// The sum of the distances between every pair of galaxies
+    pub fn sum_of_distances<M: Metric>(&self, metric: &M) -> M::Distance {
+        metric.sum_of_distances(&self.galaxies)
+    }
+
+    /// The galaxy closest to the one at `idx`, and its distance
+    pub fn nearest<M: Metric>(&self, idx: usize, metric: &M) -> Option<(usize, M::Distance)>
+    where
+        M::Distance: Ord,
+    {
+        self.distances_from(idx, metric)?
+            .min_by_key(|(_, distance)| *distance)
+    }
+
+    /// The galaxy farthest from the one at `idx`, and its distance
+    pub fn farthest<M: Metric>(&self, idx: usize, metric: &M) -> Option<(usize, M::Distance)>
+    where
+        M::Distance: Ord,
+    {
+        self.distances_from(idx, metric)?
+            .max_by_key(|(_, distance)| *distance)
+    }
+
+    fn distances_from<'a, M: Metric>(
+        &'a self,
+        idx: usize,
+        metric: &'a M,
+    ) -> Option<impl Iterator<Item = (usize, M::Distance)> + 'a> {
+        let galaxy = *self.galaxies.get(idx)?;
+        Some(
+            self.galaxies
+                .iter()
+                .enumerate()
+                .filter(move |(other, _)| *other != idx)
+                .map(move |(other, position)| (other, metric.distance(&galaxy, position))),
+        )
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{Position, Universe};
+    use crate::metric::{Chebyshev, Manhattan};
 
-    #[test]
-    fn parse_universe() {
-        let input = r#"...#......
+    use super::{Image, Position, Universe};
+
+    const INPUT: &str = r#"...#......
 .......#..
 #.........
 ..........
@@ -114,6 +182,9 @@ mod tests {
 ..........
 .......#..
 #...#....."#;
+
+    #[test]
+    fn parse_universe() {
         let expected_output = Universe {
             galaxies: vec![
                 Position::new(4, 0),
@@ -128,7 +199,36 @@ mod tests {
             ],
         };
 
-        let output = Universe::from_str(input, 2).unwrap();
+        let output = INPUT.parse::<Image>().unwrap().expand(2, 2);
         assert_eq!(output, expected_output);
     }
+
+    #[test]
+    fn expand_separately() {
+        let image: Image = INPUT.parse().unwrap();
+        assert_eq!(image.expand(1, 1).galaxies()[0], Position::new(3, 0));
+        assert_eq!(
+            image.expand(1, 10).galaxies()[5],
+            Position::new(9 + 9 * 3, 6)
+        );
+        assert_eq!(image.expand(10, 1).galaxies()[5], Position::new(9, 6 + 9));
+
+        assert_eq!(image.expand(10, 10).minimum_path_sum(), 1030);
+        assert_eq!(image.expand(100, 100).minimum_path_sum(), 8410);
+        assert!(matches!(
+            "..x".parse::<Image>(),
+            Err(super::UniverseErr::UnknownSpace('x'))
+        ));
+    }
+
+    #[test]
+    fn nearest_and_farthest() {
+        let universe = INPUT.parse::<Image>().unwrap().expand(2, 2);
+
+        assert_eq!(universe.nearest(4, &Manhattan), Some((2, 5)));
+        assert_eq!(universe.farthest(4, &Manhattan), Some((1, 13)));
+        assert_eq!(universe.nearest(0, &Chebyshev), Some((2, 4)));
+        assert_eq!(universe.nearest(9, &Manhattan), None);
+        assert_eq!(universe.farthest(9, &Chebyshev), None);
+    }
 }
@@ -1,20 +1,29 @@
+use nonogram::Nonogram;
 use rayon::{iter::ParallelIterator, str::ParallelString};
 use springs::{Record, SpringErr};
 
+mod nonogram;
 mod springs;
 
-fn part_1(input: &str) -> Result<usize, SpringErr> {
+fn part_1(input: &str) -> Result<u128, SpringErr> {
     input
         .trim()
         .par_lines()
         .map(|line| {
-            line.parse::<Record>()
-                .map(|record| record.count_arrangements())
+            line.parse::<Record>().map(|record| {
+                tracing::trace!(
+                    "{}: sampled {:?} out of {:?}",
+                    line,
+                    record.sample(&mut rand::thread_rng()),
+                    record.arrangements().collect::<Vec<_>>()
+                );
+                record.count_arrangements()
+            })
         })
         .sum()
 }
 
-fn part_2(input: &str) -> Result<usize, SpringErr> {
+fn part_2(input: &str) -> Result<u128, SpringErr> {
     input
         .trim()
         .par_lines()
@@ -28,7 +37,16 @@ fn part_2(input: &str) -> Result<usize, SpringErr> {
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let input = rust_shared_utils::read_puzzle()?;
+    tracing_subscriber::fmt::init();
+    let (input, args) = rust_shared_utils::read_puzzle_with_args()?;
+
+    // solves a nonogram in the .non format instead of the puzzle
+    if args.iter().any(|arg| arg == "--nonogram") {
+        let nonogram: Nonogram = input.parse()?;
+        print!("{}", nonogram.solve()?);
+        return Ok(());
+    }
+
     println!("part 1: {}", part_1(&input)?);
     println!("part 2: {}", part_2(&input)?);
 
@@ -0,0 +1,258 @@
+use std::{
+    fmt::{self, Display},
+    str::FromStr,
+};
+
+use crate::springs::{Record, Spring};
+
+const FILLED: char = '#';
+const BLANK: char = '.';
+
+#[derive(thiserror::Error, Debug)]
+pub enum NonogramErr {
+    #[error("missing the {0} of the nonogram")]
+    MissingSize(&'static str),
+
+    #[error("bad number in the nonogram: {0}")]
+    BadNumber(String),
+
+    #[error("expected {expected} {section} clues, found {found}")]
+    ClueCount {
+        section: &'static str,
+        expected: usize,
+        found: usize,
+    },
+
+    #[error("the nonogram has no solution")]
+    Unsolvable,
+}
+
+/// A nonogram, every row and column is a day 12 record,
+/// with the filled cells as damaged springs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Nonogram {
+    rows: Vec<Vec<usize>>,
+    cols: Vec<Vec<usize>>,
+}
+
+impl Nonogram {
+    pub fn new(rows: Vec<Vec<usize>>, cols: Vec<Vec<usize>>) -> Self {
+        Self { rows, cols }
+    }
+
+    pub fn solve(&self) -> Result<Solution, NonogramErr> {
+        let grid = vec![vec![Spring::Unknown; self.cols.len()]; self.rows.len()];
+        self.search(grid).ok_or(NonogramErr::Unsolvable)
+    }
+
+    /// Propagates the line solutions until nothing changes,
+    /// then guesses the first unknown cell and backtracks when it's wrong
+    fn search(&self, mut grid: Vec<Vec<Spring>>) -> Option<Solution> {
+        self.propagate(&mut grid)?;
+
+        let Some((ridx, cidx)) = grid.iter().enumerate().find_map(|(ridx, row)| {
+            row.iter()
+                .position(|&cell| cell == Spring::Unknown)
+                .map(|cidx| (ridx, cidx))
+        }) else {
+            return Some(Solution::new(&grid));
+        };
+
+        [Spring::Damaged, Spring::Operational]
+            .into_iter()
+            .find_map(|guess| {
+                let mut grid = grid.clone();
+                grid[ridx][cidx] = guess;
+                self.search(grid)
+            })
+    }
+
+    /// Solves every row and column on its own, until none of them
+    /// learns anything new, `None` when one of them contradicts the rest
+    fn propagate(&self, grid: &mut [Vec<Spring>]) -> Option<()> {
+        let mut changed = true;
+        while changed {
+            changed = false;
+
+            for (ridx, clues) in self.rows.iter().enumerate() {
+                let line = Record::new(grid[ridx].clone(), clues.clone()).solve_line()?;
+                changed |= line != grid[ridx];
+                grid[ridx] = line;
+            }
+
+            for (cidx, clues) in self.cols.iter().enumerate() {
+                let column = grid.iter().map(|row| row[cidx]).collect::<Vec<_>>();
+                let line = Record::new(column.clone(), clues.clone()).solve_line()?;
+                changed |= line != column;
+                for (row, cell) in grid.iter_mut().zip(line) {
+                    row[cidx] = cell;
+                }
+            }
+        }
+
+        Some(())
+    }
+}
+
+/// Reads the `.non` format, only the size and the clues are used,
+/// and the rest of the fields (title, author, goal...) are skipped
+impl FromStr for Nonogram {
+    type Err = NonogramErr;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse_number = |value: &str| {
+            value
+                .trim()
+                .parse::<usize>()
+                .map_err(|_| NonogramErr::BadNumber(value.into()))
+        };
+
+        let mut width = None;
+        let mut height = None;
+        let mut rows = vec![];
+        let mut cols = vec![];
+
+        let mut lines = s.lines().map(|line| line.trim());
+        while let Some(line) = lines.next() {
+            let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+            let section = match key {
+                "width" => {
+                    width = Some(parse_number(value)?);
+                    continue;
+                }
+                "height" => {
+                    height = Some(parse_number(value)?);
+                    continue;
+                }
+                "rows" => &mut rows,
+                "columns" => &mut cols,
+                _ => continue,
+            };
+
+            // the clues go on until the first empty line
+            for clues in lines.by_ref().take_while(|line| !line.is_empty()) {
+                section.push(
+                    clues
+                        .split(',')
+                        .map(parse_number)
+                        .filter(|size| !matches!(size, Ok(0)))
+                        .collect::<Result<Vec<_>, _>>()?,
+                );
+            }
+        }
+
+        let width = width.ok_or(NonogramErr::MissingSize("width"))?;
+        let height = height.ok_or(NonogramErr::MissingSize("height"))?;
+        for (section, clues, expected) in [("row", &rows, height), ("column", &cols, width)] {
+            if clues.len() != expected {
+                return Err(NonogramErr::ClueCount {
+                    section,
+                    expected,
+                    found: clues.len(),
+                });
+            }
+        }
+
+        Ok(Self::new(rows, cols))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Solution {
+    cells: Vec<Vec<bool>>,
+}
+
+impl Solution {
+    fn new(grid: &[Vec<Spring>]) -> Self {
+        let cells = grid
+            .iter()
+            .map(|row| row.iter().map(|&cell| cell == Spring::Damaged).collect())
+            .collect();
+
+        Self { cells }
+    }
+
+    #[cfg(test)]
+    pub fn is_filled(&self, row: usize, col: usize) -> bool {
+        self.cells[row][col]
+    }
+}
+
+/// Draws the filled cells as `#` and the blank ones as `.`
+impl Display for Solution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in &self.cells {
+            for &cell in row {
+                write!(f, "{}", if cell { FILLED } else { BLANK })?;
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Nonogram, NonogramErr};
+
+    #[test]
+    fn solve_by_propagation() {
+        let nonogram: Nonogram = r#"title "A"
+width 5
+height 5
+
+rows
+3
+1,1
+5
+1,1
+1,1
+
+columns
+4
+1,1
+1,1
+1,1
+4
+
+goal "0111010001111111000110001"
+"#
+        .parse()
+        .unwrap();
+
+        assert_eq!(
+            nonogram.solve().unwrap().to_string(),
+            ".###.\n#...#\n#####\n#...#\n#...#\n"
+        );
+    }
+
+    #[test]
+    fn solve_by_backtracking() {
+        // both diagonals fit, the line solver alone can't pick one
+        let nonogram = Nonogram::new(vec![vec![1], vec![1]], vec![vec![1], vec![1]]);
+
+        let solution = nonogram.solve().unwrap();
+        assert_eq!(solution.to_string(), "#.\n.#\n");
+        assert!(solution.is_filled(1, 1));
+
+        let nonogram = Nonogram::new(vec![vec![2], vec![]], vec![vec![1], vec![1, 1]]);
+        assert!(matches!(nonogram.solve(), Err(NonogramErr::Unsolvable)));
+    }
+
+    #[test]
+    fn parse_empty_lines() {
+        let nonogram: Nonogram = "width 2\nheight 2\nrows\n0\n2\n\ncolumns\n1\n1\n"
+            .parse()
+            .unwrap();
+
+        assert_eq!(
+            nonogram,
+            Nonogram::new(vec![vec![], vec![2]], vec![vec![1], vec![1]])
+        );
+        assert!(matches!(
+            "width 2\nheight 1\nrows\n1\n\ncolumns\n1\n".parse::<Nonogram>(),
+            Err(NonogramErr::ClueCount { .. })
+        ));
+    }
+}
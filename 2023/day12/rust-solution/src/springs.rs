@@ -1,4 +1,6 @@
-use std::{collections::HashMap, str::FromStr};
+use std::str::FromStr;
+
+use rand::Rng;
 
 const OPERATIONAL: char = '.';
 const DAMAGED: char = '#';
@@ -17,7 +19,7 @@ pub enum SpringErr {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum Spring {
+pub enum Spring {
     Operational,
     Damaged,
     Unknown,
@@ -30,6 +32,10 @@ pub struct Record {
 }
 
 impl Record {
+    pub fn new(pattern: Vec<Spring>, groups: Vec<usize>) -> Self {
+        Self { pattern, groups }
+    }
+
     pub fn unfold(&mut self, size: usize) {
         self.pattern.push(Spring::Unknown);
         let pattern_length = self.pattern.len() * size - 1;
@@ -47,53 +53,175 @@ impl Record {
             .collect();
     }
 
-    pub fn count_arrangements(&self) -> usize {
-        let last_damaged = self
-            .pattern
-            .iter()
-            .enumerate()
-            .rev()
-            .find(|(_, spring)| matches!(spring, Spring::Damaged))
-            .map(|(idx, _)| idx)
-            .unwrap_or(0);
+    pub fn count_arrangements(&self) -> u128 {
+        self.table()[0][0]
+    }
 
-        let mut mem = HashMap::new();
-        self.count_arrangements_rec(0, 0, last_damaged, &mut mem)
+    /// `table[pidx][gidx]` is the number of ways to arrange the groups
+    /// from `gidx` onwards in the pattern from `pidx` onwards
+    fn table(&self) -> Vec<Vec<u128>> {
+        let (plen, glen) = (self.pattern.len(), self.groups.len());
+        let mut table = vec![vec![0; glen + 1]; plen + 1];
+        table[plen][glen] = 1;
+
+        for pidx in (0..plen).rev() {
+            for gidx in 0..=glen {
+                table[pidx][gidx] = self
+                    .choices(pidx, gidx)
+                    .map(|(next, ngidx, _)| table[next][ngidx])
+                    .sum();
+            }
+        }
+
+        table
     }
 
-    fn count_arrangements_rec(
+    /// What can come at `pidx` when the groups from `gidx` are left,
+    /// either a single operational spring or the next group of damaged ones
+    /// (with the operational spring that must follow it), as the position and
+    /// group to continue from, and the springs that were placed.
+    fn choices(
         &self,
-        start_pidx: usize,
+        pidx: usize,
         gidx: usize,
-        last_damaged: usize,
-        mem: &mut HashMap<(usize, usize), usize>,
-    ) -> usize {
-        if let Some(count) = mem.get(&(start_pidx, gidx)) {
-            return *count;
+    ) -> impl Iterator<Item = (usize, usize, Vec<Spring>)> {
+        let operational = (self.pattern[pidx] != Spring::Damaged)
+            .then(|| (pidx + 1, gidx, vec![Spring::Operational]));
+
+        let damaged = self
+            .groups
+            .get(gidx)
+            .filter(|&&size| Self::can_fit(&self.pattern[pidx..], size))
+            .map(|&size| {
+                let mut springs = vec![Spring::Damaged; size];
+                if pidx + size < self.pattern.len() {
+                    springs.push(Spring::Operational);
+                }
+
+                (pidx + springs.len(), gidx + 1, springs)
+            });
+
+        operational.into_iter().chain(damaged)
+    }
+
+    /// `reachable[pidx][gidx]` is whether the groups before `gidx`
+    /// can be arranged in the pattern before `pidx`
+    fn reachable(&self) -> Vec<Vec<bool>> {
+        let (plen, glen) = (self.pattern.len(), self.groups.len());
+        let mut reachable = vec![vec![false; glen + 1]; plen + 1];
+        reachable[0][0] = true;
+
+        for pidx in 0..plen {
+            for gidx in 0..=glen {
+                if reachable[pidx][gidx] {
+                    for (next, ngidx, _) in self.choices(pidx, gidx) {
+                        reachable[next][ngidx] = true;
+                    }
+                }
+            }
         }
 
-        let mut count = 0;
-        for pidx in start_pidx..self.pattern.len() {
-            if Self::can_fit(&self.pattern[pidx..], self.groups[gidx]) {
-                if gidx < self.groups.len() - 1 {
-                    count += self.count_arrangements_rec(
-                        pidx + self.groups[gidx] + 1,
-                        gidx + 1,
-                        last_damaged,
-                        mem,
-                    )
-                } else if (pidx + self.groups[gidx]) >= last_damaged {
-                    count += 1;
+        reachable
+    }
+
+    /// The springs every arrangement agrees on, the rest are left unknown,
+    /// or `None` when nothing matches the record
+    pub fn solve_line(&self) -> Option<Vec<Spring>> {
+        let table = self.table();
+        if table[0][0] == 0 {
+            return None;
+        }
+
+        let plen = self.pattern.len();
+        let mut damaged = vec![false; plen];
+        let mut operational = vec![false; plen];
+        for (pidx, row) in self.reachable().into_iter().enumerate().take(plen) {
+            for gidx in (0..row.len()).filter(|&gidx| row[gidx]) {
+                for (next, ngidx, placed) in self.choices(pidx, gidx) {
+                    if table[next][ngidx] == 0 {
+                        continue;
+                    }
+
+                    for (offset, spring) in placed.into_iter().enumerate() {
+                        match spring {
+                            Spring::Damaged => damaged[pidx + offset] = true,
+                            _ => operational[pidx + offset] = true,
+                        }
+                    }
                 }
             }
+        }
+
+        Some(
+            damaged
+                .into_iter()
+                .zip(operational)
+                .map(|springs| match springs {
+                    (true, false) => Spring::Damaged,
+                    (false, true) => Spring::Operational,
+                    _ => Spring::Unknown,
+                })
+                .collect(),
+        )
+    }
+
+    /// Every arrangement that matches the record, in the same order as `nth_arrangement`
+    pub fn arrangements(&self) -> Arrangements<'_> {
+        let table = self.table();
+        let stack = match table[0][0] {
+            0 => vec![],
+            _ => vec![(0, 0, vec![])],
+        };
 
-            if matches!(self.pattern[pidx], Spring::Damaged) {
-                break;
+        Arrangements {
+            record: self,
+            table,
+            stack,
+        }
+    }
+
+    /// The arrangement at index `idx`, out of `count_arrangements`
+    pub fn nth_arrangement(&self, mut idx: u128) -> Option<String> {
+        let table = self.table();
+        if idx >= table[0][0] {
+            return None;
+        }
+
+        let (mut pidx, mut gidx) = (0, 0);
+        let mut springs = vec![];
+        while pidx < self.pattern.len() {
+            for (next, ngidx, placed) in self.choices(pidx, gidx) {
+                let count = table[next][ngidx];
+                if idx < count {
+                    (pidx, gidx) = (next, ngidx);
+                    springs.extend(placed);
+                    break;
+                }
+
+                idx -= count;
             }
         }
 
-        mem.insert((start_pidx, gidx), count);
-        count
+        Some(Self::render(&springs))
+    }
+
+    /// Picks one of the arrangements, each with the same probability
+    pub fn sample<R: Rng>(&self, rng: &mut R) -> Option<String> {
+        match self.count_arrangements() {
+            0 => None,
+            count => self.nth_arrangement(rng.gen_range(0..count)),
+        }
+    }
+
+    fn render(springs: &[Spring]) -> String {
+        springs
+            .iter()
+            .map(|spring| match spring {
+                Spring::Operational => OPERATIONAL,
+                Spring::Damaged => DAMAGED,
+                Spring::Unknown => UNKNOWN,
+            })
+            .collect()
     }
 
     // can we fit 'count' damaged springs inside the pattern, from the start
@@ -115,6 +243,40 @@ impl Record {
     }
 }
 
+/// Walks the arrangements depth first, skipping the branches without any
+pub struct Arrangements<'a> {
+    record: &'a Record,
+    table: Vec<Vec<u128>>,
+    stack: Vec<(usize, usize, Vec<Spring>)>,
+}
+
+impl Iterator for Arrangements<'_> {
+    type Item = String;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((pidx, gidx, springs)) = self.stack.pop() {
+            if pidx == self.record.pattern.len() {
+                return Some(Record::render(&springs));
+            }
+
+            let choices = self
+                .record
+                .choices(pidx, gidx)
+                .filter(|(next, ngidx, _)| self.table[*next][*ngidx] > 0)
+                .collect::<Vec<_>>();
+
+            // the first choice goes on top, to keep the order of `nth_arrangement`
+            for (next, ngidx, placed) in choices.into_iter().rev() {
+                let mut springs = springs.clone();
+                springs.extend(placed);
+                self.stack.push((next, ngidx, springs));
+            }
+        }
+
+        None
+    }
+}
+
 impl FromStr for Record {
     type Err = SpringErr;
 
@@ -146,6 +308,10 @@ impl FromStr for Record {
 
 #[cfg(test)]
 mod tests {
+    use std::collections::HashMap;
+
+    use rand::{rngs::StdRng, SeedableRng};
+
     use super::{Record, Spring};
 
     #[test]
@@ -186,4 +352,76 @@ mod tests {
             assert_eq!(input.count_arrangements(), expected);
         }
     }
+
+    #[test]
+    fn solve_line() {
+        let record: Record = "?###???????? 3,2,1".parse().unwrap();
+        assert_eq!(
+            Record::render(&record.solve_line().unwrap()),
+            ".###.???????"
+        );
+
+        let record: Record = "???????? 4,2".parse().unwrap();
+        assert_eq!(Record::render(&record.solve_line().unwrap()), "?###??#?");
+
+        let record: Record = "#.# 2".parse().unwrap();
+        assert_eq!(record.solve_line(), None);
+    }
+
+    #[test]
+    fn list_arrangements() {
+        let record: Record = "?###???????? 3,2,1".parse().unwrap();
+        let mut arrangements = record.arrangements().collect::<Vec<_>>();
+
+        for (idx, arrangement) in arrangements.iter().enumerate() {
+            assert_eq!(
+                record.nth_arrangement(idx as u128).as_ref(),
+                Some(arrangement)
+            );
+        }
+        assert_eq!(record.nth_arrangement(10), None);
+
+        // in the order the puzzle lists them
+        arrangements.sort();
+        assert_eq!(
+            arrangements,
+            vec![
+                ".###.##.#...",
+                ".###.##..#..",
+                ".###.##...#.",
+                ".###.##....#",
+                ".###..##.#..",
+                ".###..##..#.",
+                ".###..##...#",
+                ".###...##.#.",
+                ".###...##..#",
+                ".###....##.#",
+            ]
+        );
+
+        let record: Record = "#.# 2".parse().unwrap();
+        assert_eq!(record.arrangements().count(), 0);
+    }
+
+    #[test]
+    fn sample_uniformly() {
+        let record: Record = ".??..??...?##. 1,1,3".parse().unwrap();
+        let mut rng = StdRng::seed_from_u64(12);
+
+        let mut seen = HashMap::new();
+        for _ in 0..4000 {
+            *seen.entry(record.sample(&mut rng).unwrap()).or_insert(0) += 1;
+        }
+
+        assert_eq!(seen.len(), 4);
+        assert!(seen.values().all(|&count| (900..1100).contains(&count)));
+    }
+
+    #[test]
+    fn count_heavily_unfolded() {
+        let mut record: Record = "?###???????? 3,2,1".parse().unwrap();
+        record.unfold(20);
+
+        assert_eq!(record.count_arrangements(), 10 * 15u128.pow(19));
+    }
 }
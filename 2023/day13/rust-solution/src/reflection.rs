@@ -13,91 +13,113 @@ pub enum PatternErr {
 
     #[error("not all of the rows of the pattern are of the same length")]
     NotEven,
-}
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum Type {
-    Ash,
-    Rock,
+    #[error("the pattern can not have more than 64 rows or columns")]
+    TooLarge,
 }
 
+/// The rows and columns of the pattern, as bitmasks with the rocks set
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Pattern {
-    data: Vec<Vec<Type>>,
+    /// bit `c` of row `r` is the rock at column `c`
+    rows: Vec<u64>,
+    /// bit `r` of column `c` is the rock at row `r`
+    cols: Vec<u64>,
+}
+
+/// Which way the line of reflection runs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Axis {
+    /// between two rows
+    Horizontal,
+    /// between two columns
+    Vertical,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reflection {
+    pub axis: Axis,
+    /// the row or column right before the line of reflection
+    pub index: usize,
+    pub errors: usize,
+    /// the `(row, col)` of every cell that differs from its mirror image,
+    /// on the side before the line, flipping either of them fixes the reflection
+    pub mismatched_cells: Vec<(usize, usize)>,
 }
 
 impl Pattern {
     pub fn find_vertical_reflection(&self, smudge: bool) -> Option<usize> {
-        let view = PatternView::new_vertical(self);
-
-        if smudge {
-            Self::find_reflection_with_smudge(view)
-        } else {
-            Self::find_reflection(view)
-        }
+        self.find_reflection(Axis::Vertical, smudge as usize)
     }
 
     pub fn find_horizontal_reflection(&self, smudge: bool) -> Option<usize> {
-        let view = PatternView::new_horizontal(self);
+        self.find_reflection(Axis::Horizontal, smudge as usize)
+    }
 
-        if smudge {
-            Self::find_reflection_with_smudge(view)
-        } else {
-            Self::find_reflection(view)
-        }
+    fn find_reflection(&self, axis: Axis, errors: usize) -> Option<usize> {
+        self.find_reflections(errors)
+            .into_iter()
+            .find(|reflection| reflection.axis == axis && reflection.errors == errors)
+            .map(|reflection| reflection.index)
     }
 
-    fn find_reflection(view: PatternView) -> Option<usize> {
-        // for each possible start of reflection
-        for idx in 0..(view.len() - 1) {
-            let mut left = idx;
-            let mut right = idx + 1;
-
-            // compare every pair of lines until you
-            // reach the edge on either of the sides
-            while view.compare(left, right) {
-                if left == 0 || right == (view.len() - 1) {
-                    return Some(idx);
-                } else {
-                    left -= 1;
-                    right += 1;
+    /// Every line of reflection, in both orientations, that is
+    /// at most `max_errors` cells away from being a perfect one
+    pub fn find_reflections(&self, max_errors: usize) -> Vec<Reflection> {
+        [Axis::Horizontal, Axis::Vertical]
+            .into_iter()
+            .flat_map(|axis| {
+                let lines = match axis {
+                    Axis::Horizontal => &self.rows,
+                    Axis::Vertical => &self.cols,
+                };
+
+                (0..lines.len() - 1)
+                    .filter_map(move |idx| Self::reflect(lines, idx, max_errors))
+                    .map(move |(index, mismatches)| Self::reflection(axis, index, mismatches))
+            })
+            .collect()
+    }
+
+    /// Folds the lines right after `idx`, returning the differing
+    /// bits of every pair unless there are too many of them
+    fn reflect(lines: &[u64], idx: usize, max_errors: usize) -> Option<(usize, Vec<(usize, u64)>)> {
+        let mut errors = 0;
+        let mut mismatches = vec![];
+        for (left, right) in (0..=idx).rev().zip(idx + 1..lines.len()) {
+            let diff = lines[left] ^ lines[right];
+            if diff != 0 {
+                errors += diff.count_ones() as usize;
+                if errors > max_errors {
+                    return None;
                 }
+                mismatches.push((left, diff));
             }
         }
 
-        None
+        Some((idx, mismatches))
     }
 
-    fn find_reflection_with_smudge(view: PatternView) -> Option<usize> {
-        // for each possible start of reflection
-        for idx in 0..(view.len() - 1) {
-            let mut left = idx;
-            let mut right = idx + 1;
-            let mut error_count = 0;
-
-            // compare every other pair of lines until you
-            // reach the edge on either of the sides, while
-            // requiring exactly 1 error
-            loop {
-                error_count += view.count_compare(left, right);
-                if error_count > 1 {
-                    break;
-                }
-
-                if left == 0 || right == (view.len() - 1) {
-                    if error_count == 1 {
-                        return Some(idx);
-                    } else {
-                        break;
-                    }
-                } else {
-                    left -= 1;
-                    right += 1;
-                }
-            }
+    fn reflection(axis: Axis, index: usize, mismatches: Vec<(usize, u64)>) -> Reflection {
+        let mut mismatched_cells = mismatches
+            .into_iter()
+            .flat_map(|(line, diff)| {
+                (0..u64::BITS as usize)
+                    .filter(move |bit| diff & (1 << bit) != 0)
+                    .map(move |bit| match axis {
+                        Axis::Horizontal => (line, bit),
+                        Axis::Vertical => (bit, line),
+                    })
+            })
+            .collect::<Vec<_>>();
+        mismatched_cells.sort_unstable();
+
+        Reflection {
+            axis,
+            index,
+            errors: mismatched_cells.len(),
+            mismatched_cells,
         }
-
-        None
     }
 }
 
@@ -105,116 +127,56 @@ impl FromStr for Pattern {
     type Err = PatternErr;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let data = s
+        let lines = s
             .trim()
             .lines()
             .map(|line| line.trim())
             .filter(|line| !line.is_empty())
-            .map(|line| {
-                line.chars()
-                    .map(|ch| match ch {
-                        ASH => Ok(Type::Ash),
-                        ROCK => Ok(Type::Rock),
-                        _ => Err(PatternErr::UnknownType(ch)),
-                    })
-                    .collect::<Result<Vec<_>, _>>()
-            })
-            .collect::<Result<Vec<_>, _>>()?;
+            .collect::<Vec<_>>();
 
-        if data.is_empty() {
+        if lines.is_empty() {
             return Err(PatternErr::Empty);
         }
 
-        if data.iter().any(|row| row.len() != data[0].len()) {
+        let clen = lines[0].len();
+        if lines.iter().any(|line| line.len() != clen) {
             return Err(PatternErr::NotEven);
         }
 
-        Ok(Self { data })
-    }
-}
-
-// Abstract whether we're going over the rows
-// or the columns, to allow for a single
-// algorithm that can check for both directions
-struct PatternView<'a> {
-    data: &'a [Vec<Type>],
-    vertical: bool,
-}
-
-impl<'a> PatternView<'a> {
-    fn new_horizontal(pattern: &'a Pattern) -> Self {
-        Self {
-            data: &pattern.data,
-            vertical: false,
+        if lines.len() > u64::BITS as usize || clen > u64::BITS as usize {
+            return Err(PatternErr::TooLarge);
         }
-    }
 
-    fn new_vertical(pattern: &'a Pattern) -> Self {
-        Self {
-            data: &pattern.data,
-            vertical: true,
-        }
-    }
-
-    fn len(&self) -> usize {
-        match self.vertical {
-            true => self.data[0].len(),
-            false => self.data.len(),
-        }
-    }
-
-    fn compare(&self, l: usize, r: usize) -> bool {
-        match self.vertical {
-            true => self.data.iter().all(|row| row[l] == row[r]),
-            false => self.data[l] == self.data[r],
+        let mut rows = vec![0; lines.len()];
+        let mut cols = vec![0; clen];
+        for (ridx, line) in lines.into_iter().enumerate() {
+            for (cidx, ch) in line.chars().enumerate() {
+                match ch {
+                    ASH => {}
+                    ROCK => {
+                        rows[ridx] |= 1 << cidx;
+                        cols[cidx] |= 1 << ridx;
+                    }
+                    _ => return Err(PatternErr::UnknownType(ch)),
+                }
+            }
         }
-    }
 
-    fn count_compare(&self, l: usize, r: usize) -> usize {
-        match self.vertical {
-            true => self.data.iter().filter(|row| row[l] != row[r]).count(),
-            false => self.data[l]
-                .iter()
-                .zip(self.data[r].iter())
-                .filter(|(l, r)| l != r)
-                .count(),
-        }
+        Ok(Self { rows, cols })
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{Pattern, Type};
+    use super::{Axis, Pattern, Reflection};
 
     #[test]
     fn parse_pattern() {
         let input = r#"#.##..##.
 ..#.##.#."#;
         let expected_output = Pattern {
-            data: vec![
-                vec![
-                    Type::Rock,
-                    Type::Ash,
-                    Type::Rock,
-                    Type::Rock,
-                    Type::Ash,
-                    Type::Ash,
-                    Type::Rock,
-                    Type::Rock,
-                    Type::Ash,
-                ],
-                vec![
-                    Type::Ash,
-                    Type::Ash,
-                    Type::Rock,
-                    Type::Ash,
-                    Type::Rock,
-                    Type::Rock,
-                    Type::Ash,
-                    Type::Rock,
-                    Type::Ash,
-                ],
-            ],
+            rows: vec![0b011001101, 0b010110100],
+            cols: vec![0b01, 0b00, 0b11, 0b01, 0b10, 0b10, 0b01, 0b11, 0b00],
         };
 
         let output: Pattern = input.parse().unwrap();
@@ -271,4 +233,42 @@ mod tests {
         let output = input.find_horizontal_reflection(true).unwrap();
         assert_eq!(output, expected_output);
     }
+
+    #[test]
+    fn all_reflections() {
+        let input: Pattern = r#"#.##..##.
+..#.##.#.
+##......#
+##......#
+..#.##.#.
+..##..##.
+#.#.##.#."#
+            .parse()
+            .unwrap();
+
+        assert_eq!(
+            input.find_reflections(1),
+            vec![
+                Reflection {
+                    axis: Axis::Horizontal,
+                    index: 2,
+                    errors: 1,
+                    mismatched_cells: vec![(0, 0)],
+                },
+                Reflection {
+                    axis: Axis::Vertical,
+                    index: 4,
+                    errors: 0,
+                    mismatched_cells: vec![],
+                },
+            ]
+        );
+
+        let reflections = input.find_reflections(3);
+        assert!(reflections.iter().all(|reflection| reflection.errors <= 3));
+        assert!(reflections
+            .iter()
+            .all(|reflection| reflection.errors == reflection.mismatched_cells.len()));
+        assert!(reflections.len() > 2);
+    }
 }
@@ -0,0 +1,171 @@
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+    ops::Range,
+};
+
+/// A grid of bits, every row is packed into its own words
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Bitboard {
+    cols: usize,
+    words_per_row: usize,
+    words: Vec<u64>,
+}
+
+impl Bitboard {
+    pub fn new(rows: usize, cols: usize) -> Self {
+        let words_per_row = cols.div_ceil(64);
+        Self {
+            cols,
+            words_per_row,
+            words: vec![0; rows * words_per_row],
+        }
+    }
+
+    pub fn rows(&self) -> usize {
+        self.words.len() / self.words_per_row.max(1)
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    fn locate(&self, row: usize, col: usize) -> (usize, u64) {
+        debug_assert!(col < self.cols);
+        (row * self.words_per_row + col / 64, 1 << (col % 64))
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> bool {
+        let (word, mask) = self.locate(row, col);
+        self.words[word] & mask != 0
+    }
+
+    pub fn set(&mut self, row: usize, col: usize) {
+        let (word, mask) = self.locate(row, col);
+        self.words[word] |= mask;
+    }
+
+    /// The `idx`th word of the row, holding columns `64 * idx..64 * (idx + 1)`
+    pub fn word(&self, row: usize, idx: usize) -> u64 {
+        self.words[row * self.words_per_row + idx]
+    }
+
+    pub fn word_mut(&mut self, row: usize, idx: usize) -> &mut u64 {
+        &mut self.words[row * self.words_per_row + idx]
+    }
+
+    pub fn words_per_row(&self) -> usize {
+        self.words_per_row
+    }
+
+    /// The words of the row overlapping the columns, with a mask of the columns in them
+    fn range_masks(&self, row: usize, cols: Range<usize>) -> impl Iterator<Item = (usize, u64)> {
+        debug_assert!(cols.end <= self.cols);
+        let start = row * self.words_per_row;
+        (cols.start / 64..cols.end.div_ceil(64)).map(move |idx| {
+            let lo = cols.start.max(idx * 64) - idx * 64;
+            let hi = cols.end.min((idx + 1) * 64) - idx * 64;
+            let mask = if hi - lo == 64 {
+                u64::MAX
+            } else {
+                ((1 << (hi - lo)) - 1) << lo
+            };
+            (start + idx, mask)
+        })
+    }
+
+    /// The number of set bits in the columns of the row
+    pub fn count_range(&self, row: usize, cols: Range<usize>) -> usize {
+        self.range_masks(row, cols)
+            .map(|(word, mask)| (self.words[word] & mask).count_ones() as usize)
+            .sum()
+    }
+
+    pub fn set_range(&mut self, row: usize, cols: Range<usize>) {
+        for (word, mask) in self.range_masks(row, cols).collect::<Vec<_>>() {
+            self.words[word] |= mask;
+        }
+    }
+
+    pub fn clear_range(&mut self, row: usize, cols: Range<usize>) {
+        for (word, mask) in self.range_masks(row, cols).collect::<Vec<_>>() {
+            self.words[word] &= !mask;
+        }
+    }
+
+    /// The columns of the set bits in the row, in order
+    pub fn ones(&self, row: usize) -> impl Iterator<Item = usize> + '_ {
+        let start = row * self.words_per_row;
+        self.words[start..start + self.words_per_row]
+            .iter()
+            .enumerate()
+            .flat_map(|(idx, &word)| {
+                let mut word = word;
+                std::iter::from_fn(move || {
+                    (word != 0).then(|| {
+                        let bit = word.trailing_zeros() as usize;
+                        word &= word - 1;
+                        idx * 64 + bit
+                    })
+                })
+            })
+    }
+
+    /// A hash of the bits, cheaper to keep around than the board
+    pub fn fingerprint(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.words.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// The number of set bits in the row
+    pub fn count_row(&self, row: usize) -> usize {
+        let start = row * self.words_per_row;
+        self.words[start..start + self.words_per_row]
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Bitboard;
+
+    #[test]
+    fn set_and_clear() {
+        let mut board = Bitboard::new(2, 70);
+        board.set(1, 0);
+        board.set(1, 69);
+        board.set(0, 64);
+        board.clear_range(1, 0..1);
+
+        assert_eq!(board.rows(), 2);
+        assert!(board.get(1, 69));
+        assert!(!board.get(1, 0));
+        assert_eq!(board.count_row(0), 1);
+        assert_eq!(board.count_row(1), 1);
+    }
+
+    #[test]
+    fn ranges_across_words() {
+        let mut board = Bitboard::new(1, 130);
+        board.set_range(0, 60..129);
+        assert_eq!(board.count_range(0, 0..130), 69);
+        assert_eq!(board.count_range(0, 64..128), 64);
+        assert!(!board.get(0, 59) && board.get(0, 60) && board.get(0, 128) && !board.get(0, 129));
+
+        board.clear_range(0, 62..127);
+        assert_eq!(board.ones(0).collect::<Vec<_>>(), vec![60, 61, 127, 128]);
+    }
+
+    #[test]
+    fn fingerprint() {
+        let mut board = Bitboard::new(3, 70);
+        let empty = board.fingerprint();
+        board.set(2, 65);
+        assert_ne!(board.fingerprint(), empty);
+        board.clear_range(2, 65..66);
+        assert_eq!(board.fingerprint(), empty);
+    }
+}
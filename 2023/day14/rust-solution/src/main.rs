@@ -1,7 +1,6 @@
-use std::collections::HashMap;
-
 use prd::{Platform, PrdErr};
 
+mod bitboard;
 mod prd;
 
 fn part_1(input: &str) -> Result<usize, PrdErr> {
@@ -13,25 +12,9 @@ fn part_1(input: &str) -> Result<usize, PrdErr> {
 
 fn part_2(input: &str) -> Result<usize, PrdErr> {
     let mut platform: Platform = input.parse()?;
+    platform.spin(1000000000);
 
-    let mut visited = HashMap::new();
-    for idx in 0.. {
-        if let Some(pidx) = visited.insert(platform.clone(), idx) {
-            let cycle_length = idx - pidx;
-            let goal = 1000000000;
-            let after_cycle = (goal - pidx) % cycle_length;
-
-            for _ in 0..after_cycle {
-                platform.tilt_cycle();
-            }
-
-            return Ok(platform.load_on_north_support_beam());
-        }
-
-        platform.tilt_cycle();
-    }
-
-    unreachable!()
+    Ok(platform.load_on_north_support_beam())
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -1,4 +1,11 @@
-use std::{collections::VecDeque, str::FromStr};
+use std::{
+    collections::HashMap,
+    fmt::{self, Display},
+    ops::Range,
+    str::FromStr,
+};
+
+use crate::bitboard::Bitboard;
 
 const CUBE: char = '#';
 const ROUNDED: char = 'O';
@@ -16,16 +23,11 @@ pub enum PrdErr {
     NotEven,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-enum Space {
-    Cube,
-    Rounded,
-    Empty,
-}
-
+/// The rounded and cube rocks on the platform, a bit per tile
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Platform {
-    data: Vec<Vec<Space>>,
+    rounded: Bitboard,
+    cubes: Bitboard,
 }
 
 impl Platform {
@@ -36,94 +38,87 @@ impl Platform {
         self.tilt_east();
     }
 
-    pub fn tilt_north(&mut self) {
-        for cidx in 0..self.data[0].len() {
-            let mut empty = VecDeque::new();
-            for ridx in 0..self.data.len() {
-                match self.data[ridx][cidx] {
-                    Space::Empty => empty.push_back(ridx),
-                    Space::Cube => empty.clear(),
-                    Space::Rounded => {
-                        if let Some(new_ridx) = empty.pop_front() {
-                            self.data[new_ridx][cidx] = Space::Rounded;
-                            self.data[ridx][cidx] = Space::Empty;
-                            empty.push_back(ridx);
-                        }
-                    }
+    /// Runs `n` tilt cycles, skipping ahead once the platform repeats itself
+    pub fn spin(&mut self, n: usize) {
+        let mut visited = HashMap::new();
+        for idx in 0..n {
+            if let Some(pidx) = visited.insert(self.rounded.fingerprint(), idx) {
+                for _ in 0..(n - idx) % (idx - pidx) {
+                    self.tilt_cycle();
                 }
+
+                return;
             }
+
+            self.tilt_cycle();
         }
     }
 
+    pub fn tilt_north(&mut self) {
+        let rows = (0..self.rounded.rows()).collect::<Vec<_>>();
+        self.roll_rows(&rows);
+    }
+
     pub fn tilt_west(&mut self) {
-        for ridx in 0..self.data.len() {
-            let mut empty = VecDeque::new();
-            for cidx in 0..self.data[ridx].len() {
-                match self.data[ridx][cidx] {
-                    Space::Empty => empty.push_back(cidx),
-                    Space::Cube => empty.clear(),
-                    Space::Rounded => {
-                        if let Some(new_cidx) = empty.pop_front() {
-                            self.data[ridx][new_cidx] = Space::Rounded;
-                            self.data[ridx][cidx] = Space::Empty;
-                            empty.push_back(cidx);
-                        }
-                    }
-                }
-            }
-        }
+        self.slide_cols(|segment, count| segment.start..segment.start + count);
     }
 
     pub fn tilt_south(&mut self) {
-        for cidx in 0..self.data[0].len() {
-            let mut empty = VecDeque::new();
-            for ridx in (0..self.data.len()).rev() {
-                match self.data[ridx][cidx] {
-                    Space::Empty => empty.push_back(ridx),
-                    Space::Cube => empty.clear(),
-                    Space::Rounded => {
-                        if let Some(new_ridx) = empty.pop_front() {
-                            self.data[new_ridx][cidx] = Space::Rounded;
-                            self.data[ridx][cidx] = Space::Empty;
-                            empty.push_back(ridx);
-                        }
-                    }
+        let rows = (0..self.rounded.rows()).rev().collect::<Vec<_>>();
+        self.roll_rows(&rows);
+    }
+
+    pub fn tilt_east(&mut self) {
+        self.slide_cols(|segment, count| segment.end - count..segment.end);
+    }
+
+    /// Rolls the rounded rocks a whole row at a time towards the first row in `order`,
+    /// every rock keeps moving up the rows until a rock or a cube blocks it.
+    fn roll_rows(&mut self, order: &[usize]) {
+        for idx in 0..order.len() {
+            for widx in 0..self.rounded.words_per_row() {
+                let mut moving = std::mem::take(self.rounded.word_mut(order[idx], widx));
+                let mut at = idx;
+                while at > 0 && moving != 0 {
+                    let ahead = order[at - 1];
+                    let blocked = self.rounded.word(ahead, widx) | self.cubes.word(ahead, widx);
+                    *self.rounded.word_mut(order[at], widx) |= moving & blocked;
+                    moving &= !blocked;
+                    at -= 1;
                 }
+
+                *self.rounded.word_mut(order[at], widx) |= moving;
             }
         }
     }
 
-    pub fn tilt_east(&mut self) {
-        for ridx in 0..self.data.len() {
-            let mut empty = VecDeque::new();
-            for cidx in (0..self.data[ridx].len()).rev() {
-                match self.data[ridx][cidx] {
-                    Space::Empty => empty.push_back(cidx),
-                    Space::Cube => empty.clear(),
-                    Space::Rounded => {
-                        if let Some(new_cidx) = empty.pop_front() {
-                            self.data[ridx][new_cidx] = Space::Rounded;
-                            self.data[ridx][cidx] = Space::Empty;
-                            empty.push_back(cidx);
-                        }
-                    }
+    /// Gathers the rounded rocks between every pair of cubes in a row,
+    /// `packed` picks the columns of the segment they end up in.
+    fn slide_cols(&mut self, packed: impl Fn(Range<usize>, usize) -> Range<usize>) {
+        let (rlen, clen) = self.len();
+        for ridx in 0..rlen {
+            let ends = self.cubes.ones(ridx).chain([clen]).collect::<Vec<_>>();
+            let mut start = 0;
+            for end in ends {
+                let count = self.rounded.count_range(ridx, start..end);
+                if count > 0 {
+                    self.rounded.clear_range(ridx, start..end);
+                    self.rounded.set_range(ridx, packed(start..end, count));
                 }
+                start = end + 1;
             }
         }
     }
 
     pub fn load_on_north_support_beam(&self) -> usize {
-        self.data
-            .iter()
-            .rev()
-            .enumerate()
-            .map(|(ridx, line)| {
-                line.iter()
-                    .filter(|space| matches!(space, Space::Rounded))
-                    .count()
-                    * (ridx + 1)
-            })
-            .sum::<usize>()
+        let rlen = self.rounded.rows();
+        (0..rlen)
+            .map(|ridx| self.rounded.count_row(ridx) * (rlen - ridx))
+            .sum()
+    }
+
+    pub fn len(&self) -> (usize, usize) {
+        (self.rounded.rows(), self.rounded.cols())
     }
 }
 
@@ -131,90 +126,80 @@ impl FromStr for Platform {
     type Err = PrdErr;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let data = s
+        let lines = s
             .trim()
             .lines()
             .map(|line| line.trim())
             .filter(|line| !line.is_empty())
-            .map(|line| {
-                line.chars()
-                    .map(|ch| match ch {
-                        CUBE => Ok(Space::Cube),
-                        ROUNDED => Ok(Space::Rounded),
-                        EMPTY => Ok(Space::Empty),
-                        _ => Err(PrdErr::UnknownSpaceValue(ch)),
-                    })
-                    .collect::<Result<Vec<_>, _>>()
-            })
-            .collect::<Result<Vec<_>, _>>()?;
+            .collect::<Vec<_>>();
 
-        if data.is_empty() {
+        if lines.is_empty() {
             return Err(PrdErr::Empty);
         }
 
-        let rlen = data[0].len();
-        if data.iter().any(|line| line.len() != rlen) {
+        let clen = lines[0].len();
+        if lines.iter().any(|line| line.len() != clen) {
             return Err(PrdErr::NotEven);
         }
 
-        Ok(Self { data })
+        let mut rounded = Bitboard::new(lines.len(), clen);
+        let mut cubes = Bitboard::new(lines.len(), clen);
+        for (ridx, line) in lines.into_iter().enumerate() {
+            for (cidx, ch) in line.chars().enumerate() {
+                match ch {
+                    CUBE => cubes.set(ridx, cidx),
+                    ROUNDED => rounded.set(ridx, cidx),
+                    EMPTY => {}
+                    _ => return Err(PrdErr::UnknownSpaceValue(ch)),
+                }
+            }
+        }
+
+        Ok(Self { rounded, cubes })
+    }
+}
+
+impl Display for Platform {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (rlen, clen) = self.len();
+        for ridx in 0..rlen {
+            for cidx in 0..clen {
+                let ch = match (self.cubes.get(ridx, cidx), self.rounded.get(ridx, cidx)) {
+                    (true, _) => CUBE,
+                    (_, true) => ROUNDED,
+                    _ => EMPTY,
+                };
+                write!(f, "{}", ch)?;
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{Platform, Space};
+    use super::Platform;
 
     #[test]
     fn parse_platform() {
         let input = r#"O....
         O.OO#"#;
 
-        let expected_output = Platform {
-            data: vec![
-                vec![
-                    Space::Rounded,
-                    Space::Empty,
-                    Space::Empty,
-                    Space::Empty,
-                    Space::Empty,
-                ],
-                vec![
-                    Space::Rounded,
-                    Space::Empty,
-                    Space::Rounded,
-                    Space::Rounded,
-                    Space::Cube,
-                ],
-            ],
-        };
-
         let output: Platform = input.parse().unwrap();
-        assert_eq!(output, expected_output);
+        assert_eq!(output.len(), (2, 5));
+        assert_eq!(output.to_string(), "O....\nO.OO#\n");
     }
 
     #[test]
     fn tilt_north_simple() {
         let input = r#"O....
 O.OO#"#;
-        let expected_output = Platform {
-            data: vec![
-                vec![
-                    Space::Rounded,
-                    Space::Empty,
-                    Space::Rounded,
-                    Space::Rounded,
-                    Space::Empty,
-                ],
-                vec![
-                    Space::Rounded,
-                    Space::Empty,
-                    Space::Empty,
-                    Space::Empty,
-                    Space::Cube,
-                ],
-            ],
-        };
+        let expected_output: Platform = r#"O.OO.
+O...#"#
+            .parse()
+            .unwrap();
 
         let mut output: Platform = input.parse().unwrap();
         output.tilt_north();
@@ -244,22 +229,7 @@ O.#..O.#.#
     #[test]
     fn tilt_west() {
         let input: Platform = ".O.O#.O#O..O".parse().unwrap();
-        let expected_output = Platform {
-            data: vec![vec![
-                Space::Rounded,
-                Space::Rounded,
-                Space::Empty,
-                Space::Empty,
-                Space::Cube,
-                Space::Rounded,
-                Space::Empty,
-                Space::Cube,
-                Space::Rounded,
-                Space::Rounded,
-                Space::Empty,
-                Space::Empty,
-            ]],
-        };
+        let expected_output: Platform = "OO..#O.#OO..".parse().unwrap();
 
         let mut output = input;
         output.tilt_west();
@@ -283,54 +253,91 @@ O
 ."#
         .parse()
         .unwrap();
-        let expected_output = Platform {
-            data: vec![
-                vec![Space::Empty],
-                vec![Space::Empty],
-                vec![Space::Empty],
-                vec![Space::Empty],
-                vec![Space::Rounded],
-                vec![Space::Rounded],
-                vec![Space::Cube],
-                vec![Space::Empty],
-                vec![Space::Rounded],
-                vec![Space::Cube],
-                vec![Space::Empty],
-                vec![Space::Empty],
-                vec![Space::Rounded],
-            ],
-        };
+        let expected_output = "....OO#.O#..O"
+            .chars()
+            .map(|ch| format!("{}\n", ch))
+            .collect::<String>();
 
         let mut output = input;
         output.tilt_south();
-        assert_eq!(output, expected_output);
+        assert_eq!(output.to_string(), expected_output);
     }
 
     #[test]
     fn tilt_east() {
         let input: Platform = "O.O.#.O#O..O".parse().unwrap();
-        let expected_output = Platform {
-            data: vec![vec![
-                Space::Empty,
-                Space::Empty,
-                Space::Rounded,
-                Space::Rounded,
-                Space::Cube,
-                Space::Empty,
-                Space::Rounded,
-                Space::Cube,
-                Space::Empty,
-                Space::Empty,
-                Space::Rounded,
-                Space::Rounded,
-            ]],
-        };
+        let expected_output: Platform = "..OO#.O#..OO".parse().unwrap();
 
         let mut output = input;
         output.tilt_east();
         assert_eq!(output, expected_output);
     }
 
+    /// Rolls the rocks of every line in the text to the start or the end of the line
+    fn roll_lines(grid: &str, to_start: bool) -> String {
+        grid.lines()
+            .map(|line| {
+                let segments = line.split('#').map(|segment| {
+                    let rocks = segment.matches('O').count();
+                    let (rocks, empty) = ("O".repeat(rocks), ".".repeat(segment.len() - rocks));
+                    if to_start {
+                        rocks + &empty
+                    } else {
+                        empty + &rocks
+                    }
+                });
+                segments.collect::<Vec<_>>().join("#") + "\n"
+            })
+            .collect()
+    }
+
+    fn transpose(grid: &str) -> String {
+        let lines = grid.lines().map(str::as_bytes).collect::<Vec<_>>();
+        (0..lines[0].len())
+            .map(|cidx| {
+                let column = lines.iter().map(|line| line[cidx] as char);
+                column.chain(['\n']).collect::<String>()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn tilt_across_words() {
+        let row = format!("O.{}#.O.O#{}.O", ".".repeat(62), "O".repeat(60));
+        let grid = [
+            row.clone(),
+            row.replace('#', "."),
+            row.chars().rev().collect(),
+        ]
+        .join("\n");
+        let platform: Platform = grid.parse().unwrap();
+
+        let mut output = platform.clone();
+        output.tilt_west();
+        assert_eq!(output.to_string(), roll_lines(&grid, true));
+
+        let mut output = platform;
+        output.tilt_east();
+        assert_eq!(output.to_string(), roll_lines(&grid, false));
+
+        let grid = transpose(&grid);
+        let platform: Platform = grid.parse().unwrap();
+
+        let mut output = platform.clone();
+        output.tilt_north();
+        assert_eq!(
+            output.to_string(),
+            transpose(&roll_lines(&transpose(&grid), true))
+        );
+
+        let mut output = platform;
+        output.tilt_south();
+        assert_eq!(
+            output.to_string(),
+            transpose(&roll_lines(&transpose(&grid), false))
+        );
+    }
+
     #[test]
     fn complete_cycle() {
         let input: Platform = r#"O....#....
@@ -381,4 +388,31 @@ O.#..O.#.#
         output.tilt_cycle();
         assert_eq!(output, expected_output);
     }
+
+    #[test]
+    fn spin_billion() {
+        let mut platform: Platform = r#"O....#....
+O.OO#....#
+.....##...
+OO.#O....O
+.O.....O#.
+O.#..O.#.#
+..O..#O..O
+.......O..
+#....###..
+#OO..#...."#
+            .parse()
+            .unwrap();
+
+        let mut slow = platform.clone();
+        for _ in 0..30 {
+            slow.tilt_cycle();
+        }
+        let mut fast = platform.clone();
+        fast.spin(30);
+        assert_eq!(fast, slow);
+
+        platform.spin(1_000_000_000);
+        assert_eq!(platform.load_on_north_support_beam(), 64);
+    }
 }
@@ -0,0 +1,153 @@
+use std::{
+    collections::BTreeMap,
+    hash::{BuildHasher, BuildHasherDefault, Hasher},
+};
+
+/// The inverse of 17 modulo 256, undoes the multiplication of a HASH step
+const INVERSE_17: u8 = 241;
+
+/// A single HASH step, folding the byte into the current value
+pub fn step(value: u8, byte: u8) -> u8 {
+    value.wrapping_add(byte).wrapping_mul(17)
+}
+
+/// The byte that takes the current value to `target` in a single step
+fn preimage(value: u8, target: u8) -> u8 {
+    target.wrapping_mul(INVERSE_17).wrapping_sub(value)
+}
+
+/// The HASH algorithm as a streaming `Hasher`, so it can drive std collections.
+/// Keep in mind `str` and `String` write an extra `0xff` byte when hashed through
+/// `Hash`, so only `write` on the raw bytes matches `initialization::hash`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct HolidayHasher {
+    value: u8,
+}
+
+impl Hasher for HolidayHasher {
+    fn finish(&self) -> u64 {
+        self.value as u64
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        self.value = bytes
+            .iter()
+            .fold(self.value, |value, &byte| step(value, byte));
+    }
+}
+
+pub type BuildHolidayHasher = BuildHasherDefault<HolidayHasher>;
+
+/// Every lowercase label that hashes to the box, shortest first
+pub fn labels_for_box(ibox: u8) -> impl Iterator<Item = String> {
+    // every prefix, counting in base 26 with `a` as the first digit,
+    // has at most one last letter that lands it in the box
+    let mut prefix: Vec<u8> = vec![];
+    std::iter::from_fn(move || {
+        let value = prefix.iter().fold(0, |value, &byte| step(value, byte));
+        let last = preimage(value, ibox);
+
+        let label = last.is_ascii_lowercase().then(|| {
+            let mut label = String::from_utf8(prefix.clone()).unwrap();
+            label.push(last as char);
+            label
+        });
+
+        match prefix.iter().rposition(|&byte| byte != b'z') {
+            Some(idx) => {
+                prefix[idx] += 1;
+                prefix[idx + 1..].fill(b'a');
+            }
+            None => prefix = vec![b'a'; prefix.len() + 1],
+        }
+
+        Some(label)
+    })
+    .flatten()
+}
+
+/// Groups the distinct labels by what `build`'s hashers make of their bytes,
+/// hashes with a single label are left out
+pub fn find_collisions<'a, S: BuildHasher>(
+    labels: impl IntoIterator<Item = &'a str>,
+    build: &S,
+) -> Vec<(u64, Vec<&'a str>)> {
+    let mut boxes: BTreeMap<u64, Vec<&str>> = BTreeMap::new();
+    for label in labels {
+        let mut hasher = build.build_hasher();
+        hasher.write(label.as_bytes());
+
+        let ibox = boxes.entry(hasher.finish()).or_default();
+        if !ibox.contains(&label) {
+            ibox.push(label);
+        }
+    }
+
+    boxes
+        .into_iter()
+        .filter(|(_, labels)| labels.len() > 1)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, hash::Hasher};
+
+    use proptest::prelude::*;
+
+    use crate::initialization::hash;
+
+    use super::{find_collisions, labels_for_box, BuildHolidayHasher, HolidayHasher};
+
+    #[test]
+    fn labels_in_box() {
+        let labels = labels_for_box(3).take(50).collect::<Vec<_>>();
+        assert!(labels.iter().all(|label| hash(label) == 3));
+        assert!(labels.windows(2).all(|w| w[0].len() <= w[1].len()));
+        assert!(labels.contains(&"pc".to_string()));
+        assert!(labels.contains(&"ot".to_string()));
+    }
+
+    #[test]
+    fn collisions() {
+        let labels = "rn,cm,qp,pc,ot,ab,rn".split(',');
+        assert_eq!(
+            find_collisions(labels, &BuildHolidayHasher::default()),
+            vec![(0, vec!["rn", "cm"]), (3, vec!["pc", "ot", "ab"])]
+        );
+    }
+
+    #[test]
+    fn build_hasher() {
+        let mut lenses: HashMap<&str, u8, BuildHolidayHasher> = HashMap::default();
+        lenses.insert("rn", 1);
+        lenses.insert("cm", 2);
+        assert_eq!(lenses.get("cm"), Some(&2));
+    }
+
+    proptest! {
+        #[test]
+        fn hasher_matches_fold(label in "[ -~]*") {
+            let mut hasher = HolidayHasher::default();
+            hasher.write(label.as_bytes());
+            prop_assert_eq!(hasher.finish(), hash(&label) as u64);
+        }
+
+        #[test]
+        fn hasher_streams(label in "[ -~]*", split in 0usize..64) {
+            let (head, tail) = label.as_bytes().split_at(split.min(label.len()));
+
+            let mut hasher = HolidayHasher::default();
+            hasher.write(head);
+            hasher.write(tail);
+            prop_assert_eq!(hasher.finish(), hash(&label) as u64);
+        }
+
+        #[test]
+        fn labels_hash_to_box(ibox in any::<u8>()) {
+            for label in labels_for_box(ibox).take(10) {
+                prop_assert_eq!(hash(&label), ibox);
+            }
+        }
+    }
+}
@@ -1,5 +1,5 @@
 use std::{
-    collections::{hash_map, HashMap},
+    fmt::{self, Display},
     str::FromStr,
 };
 
@@ -9,26 +9,26 @@ pub fn hash(data: &str) -> u8 {
     }) as u8
 }
 
-#[derive(Debug, PartialEq, Eq, Clone)]
+/// The lenses in a box, in the order they were put in
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
 pub struct IBox {
-    data: HashMap<String, (u32, u8)>,
-    next_id: u32,
+    lenses: Vec<(String, u8)>,
 }
 
 impl IBox {
     pub fn new() -> Self {
-        Self {
-            data: Default::default(),
-            next_id: 0,
-        }
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lenses.is_empty()
     }
 
     pub fn focusing_power(&self) -> u32 {
-        let mut lens = self.data.values().copied().collect::<Vec<_>>();
-        lens.sort();
-        lens.into_iter()
+        self.lenses
+            .iter()
             .enumerate()
-            .map(|(idx, (_, focal))| (idx as u32 + 1) * focal as u32)
+            .map(|(idx, (_, focal))| (idx as u32 + 1) * *focal as u32)
             .sum()
     }
 
@@ -40,22 +40,27 @@ impl IBox {
     }
 
     pub fn insert(&mut self, label: String, focal: u8) {
-        match self.data.entry(label) {
-            hash_map::Entry::Occupied(mut entry) => {
-                let id = entry.get().0;
-                entry.insert((id, focal));
-            }
-            hash_map::Entry::Vacant(entry) => {
-                let id = self.next_id;
-                self.next_id += 1;
-
-                entry.insert((id, focal));
-            }
+        match self.lenses.iter_mut().find(|(other, _)| *other == label) {
+            Some(lens) => lens.1 = focal,
+            None => self.lenses.push((label, focal)),
         }
     }
 
     pub fn remove(&mut self, label: &str) {
-        self.data.remove(label);
+        self.lenses.retain(|(other, _)| other != label);
+    }
+}
+
+/// Displays the lenses as `[ot 7] [ab 5]`
+impl Display for IBox {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let lenses = self
+            .lenses
+            .iter()
+            .map(|(label, focal)| format!("[{} {}]", label, focal))
+            .collect::<Vec<_>>();
+
+        write!(f, "{}", lenses.join(" "))
     }
 }
 
@@ -81,11 +86,25 @@ pub struct Step {
 }
 
 impl Step {
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
     pub fn ibox(&self) -> u8 {
         hash(&self.label)
     }
 }
 
+/// Displays the step as it appears in the initialization sequence
+impl Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.method {
+            Method::Insert(focal) => write!(f, "{}={}", self.label, focal),
+            Method::Remove => write!(f, "{}-", self.label),
+        }
+    }
+}
+
 impl FromStr for Step {
     type Err = StepErr;
 
@@ -146,6 +165,7 @@ mod tests {
         for (input, expected) in inputs.into_iter().zip(expected_outputs) {
             let output: Step = input.parse().unwrap();
             assert_eq!(output, expected);
+            assert_eq!(output.to_string(), input);
         }
     }
 
@@ -161,5 +181,8 @@ mod tests {
         ibox.remove("test4");
         ibox.remove("test2");
         assert_eq!(ibox.focusing_power(), 2 + 8);
+
+        ibox.insert("test".into(), 5);
+        assert_eq!(ibox.to_string(), "[test 5] [test3 4]");
     }
 }
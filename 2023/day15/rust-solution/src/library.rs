@@ -0,0 +1,174 @@
+use std::fmt::{self, Display};
+
+use crate::initialization::{IBox, Step};
+
+const BOXES: usize = 256;
+
+/// The boxes lined up in the facility, indexed by the hash of the lens labels
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct LensLibrary {
+    boxes: Vec<IBox>,
+}
+
+impl LensLibrary {
+    pub fn new() -> Self {
+        Self {
+            boxes: vec![IBox::new(); BOXES],
+        }
+    }
+
+    pub fn execute(&mut self, step: Step) {
+        self.boxes[step.ibox() as usize].execute(step);
+    }
+
+    pub fn focusing_power(&self) -> u32 {
+        self.boxes
+            .iter()
+            .enumerate()
+            .map(|(idx, ibox)| ibox.focusing_power() * (idx as u32 + 1))
+            .sum()
+    }
+
+    /// Executes the steps one by one, the library can be looked at after each one
+    pub fn trace<I>(self, steps: I) -> Trace<I::IntoIter>
+    where
+        I: IntoIterator<Item = Step>,
+    {
+        Trace {
+            library: self,
+            steps: steps.into_iter(),
+        }
+    }
+}
+
+/// Displays the boxes that have lenses, as `Box 3: [ot 7] [ab 5]`
+impl Display for LensLibrary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (idx, ibox) in self.boxes.iter().enumerate() {
+            if !ibox.is_empty() {
+                writeln!(f, "Box {}: {}", idx, ibox)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// A step and the library right after it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Snapshot<'a> {
+    pub step: &'a Step,
+    pub library: &'a LensLibrary,
+}
+
+/// Display as the puzzle's walkthrough does
+impl Display for Snapshot<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "After \"{}\":", self.step)?;
+        write!(f, "{}", self.library)
+    }
+}
+
+/// Executes the steps one by one, yielding every step once it's done
+pub struct Trace<I> {
+    library: LensLibrary,
+    steps: I,
+}
+
+impl<I> Trace<I> {
+    /// The library after the steps executed so far
+    pub fn library(&self) -> &LensLibrary {
+        &self.library
+    }
+}
+
+impl<I: Iterator<Item = Step>> Iterator for Trace<I> {
+    type Item = Step;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let step = self.steps.next()?;
+        self.library.execute(step.clone());
+        Some(step)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::initialization::Step;
+
+    use super::{LensLibrary, Snapshot};
+
+    const INPUT: &str = "rn=1,cm-,qp=3,cm=2,qp-,pc=4,ot=9,ab=5,pc-,pc=6,ot=7";
+
+    /// The states from the puzzle's walkthrough
+    const WALKTHROUGH: &str = r#"After "rn=1":
+Box 0: [rn 1]
+
+After "cm-":
+Box 0: [rn 1]
+
+After "qp=3":
+Box 0: [rn 1]
+Box 1: [qp 3]
+
+After "cm=2":
+Box 0: [rn 1] [cm 2]
+Box 1: [qp 3]
+
+After "qp-":
+Box 0: [rn 1] [cm 2]
+
+After "pc=4":
+Box 0: [rn 1] [cm 2]
+Box 3: [pc 4]
+
+After "ot=9":
+Box 0: [rn 1] [cm 2]
+Box 3: [pc 4] [ot 9]
+
+After "ab=5":
+Box 0: [rn 1] [cm 2]
+Box 3: [pc 4] [ot 9] [ab 5]
+
+After "pc-":
+Box 0: [rn 1] [cm 2]
+Box 3: [ot 9] [ab 5]
+
+After "pc=6":
+Box 0: [rn 1] [cm 2]
+Box 3: [ot 9] [ab 5] [pc 6]
+
+After "ot=7":
+Box 0: [rn 1] [cm 2]
+Box 3: [ot 7] [ab 5] [pc 6]
+"#;
+
+    #[test]
+    fn trace_walkthrough() {
+        let steps = INPUT
+            .split(',')
+            .map(|step| step.parse::<Step>().unwrap())
+            .collect::<Vec<_>>();
+
+        let expected = WALKTHROUGH.split("\n\n").collect::<Vec<_>>();
+        let mut trace = LensLibrary::new().trace(steps);
+        let mut snapshots = vec![];
+        while let Some(step) = trace.next() {
+            let library = trace.library();
+            snapshots.push(
+                Snapshot {
+                    step: &step,
+                    library,
+                }
+                .to_string(),
+            );
+        }
+
+        assert_eq!(snapshots.len(), expected.len());
+        for (snapshot, expected) in snapshots.iter().zip(expected) {
+            assert_eq!(snapshot.trim_end(), expected.trim_end());
+        }
+
+        assert_eq!(trace.library().focusing_power(), 145);
+    }
+}
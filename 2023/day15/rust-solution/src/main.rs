@@ -1,6 +1,10 @@
-use initialization::{IBox, Step, StepErr};
+use hasher::BuildHolidayHasher;
+use initialization::{Step, StepErr};
+use library::{LensLibrary, Snapshot};
 
+mod hasher;
 mod initialization;
+mod library;
 
 fn part_1(input: &str) -> u32 {
     input
@@ -11,22 +15,68 @@ fn part_1(input: &str) -> u32 {
 }
 
 fn part_2(input: &str) -> Result<u32, StepErr> {
-    let mut boxes = vec![IBox::new(); 256];
+    let steps = input
+        .trim()
+        .split(',')
+        .map(|step| step.parse())
+        .collect::<Result<Vec<Step>, _>>()?;
 
-    for step in input.trim().split(',') {
-        let step: Step = step.parse()?;
-        boxes[step.ibox() as usize].execute(step);
+    let mut trace = LensLibrary::new().trace(steps);
+    while let Some(step) = trace.next() {
+        let library = trace.library();
+        tracing::trace!(
+            "{}",
+            Snapshot {
+                step: &step,
+                library
+            }
+        );
     }
 
-    Ok(boxes
-        .iter()
-        .enumerate()
-        .map(|(idx, ibox)| ibox.focusing_power() * (idx as u32 + 1))
-        .sum())
+    Ok(trace.library().focusing_power())
+}
+
+/// How many labels `--labels` lists
+const LABELS: usize = 10;
+
+/// The labels of the steps that end up in the same box
+fn collisions(input: &str) -> Result<Vec<(u64, Vec<String>)>, StepErr> {
+    let steps = input
+        .trim()
+        .split(',')
+        .map(|step| step.parse())
+        .collect::<Result<Vec<Step>, _>>()?;
+
+    let collisions = hasher::find_collisions(
+        steps.iter().map(Step::label),
+        &BuildHolidayHasher::default(),
+    );
+    Ok(collisions
+        .into_iter()
+        .map(|(ibox, labels)| (ibox, labels.into_iter().map(String::from).collect()))
+        .collect())
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let input = rust_shared_utils::read_puzzle()?;
+    tracing_subscriber::fmt::init();
+    let (input, args) = rust_shared_utils::read_puzzle_with_args()?;
+
+    // lists the shortest labels that land in the box, to build inputs with
+    if let Some(idx) = args.iter().position(|arg| arg == "--labels") {
+        let ibox: u8 = args.get(idx + 1).map_or("", String::as_str).parse()?;
+        for label in hasher::labels_for_box(ibox).take(LABELS) {
+            println!("{}", label);
+        }
+        return Ok(());
+    }
+
+    if args.iter().any(|arg| arg == "--collisions") {
+        for (ibox, labels) in collisions(&input)? {
+            println!("box {}: {}", ibox, labels.join(", "));
+        }
+        return Ok(());
+    }
+
     println!("part 1: {}", part_1(&input));
     println!("part 2: {}", part_2(&input)?);
 
@@ -45,4 +95,18 @@ mod tests {
     fn part_2() {
         assert_eq!(super::part_2(INPUT).unwrap(), 145);
     }
+
+    #[test]
+    fn collisions() {
+        assert_eq!(
+            super::collisions(INPUT).unwrap(),
+            vec![
+                (0, vec!["rn".to_string(), "cm".to_string()]),
+                (
+                    3,
+                    vec!["pc".to_string(), "ot".to_string(), "ab".to_string()]
+                )
+            ]
+        );
+    }
 }
@@ -0,0 +1,82 @@
+use crate::contraption::Dir;
+
+/// The directions of the beams passing through every tile of the grid
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Beams {
+    /// a bit per direction, in the order of `Dir::ALL`
+    data: Vec<Vec<u8>>,
+}
+
+impl Beams {
+    pub fn new(rows: usize, cols: usize) -> Self {
+        Self {
+            data: vec![vec![0; cols]; rows],
+        }
+    }
+
+    /// Records the beam, returns false if it already passed there
+    pub fn insert(&mut self, (row, col): (usize, usize), dir: Dir) -> bool {
+        let bit = 1 << dir as u8;
+        let tile = &mut self.data[row][col];
+        let new = *tile & bit == 0;
+        *tile |= bit;
+        new
+    }
+
+    pub fn dirs(&self, (row, col): (usize, usize)) -> impl Iterator<Item = Dir> + '_ {
+        let tile = self.data[row][col];
+        Dir::ALL
+            .into_iter()
+            .filter(move |&dir| tile & (1 << dir as u8) != 0)
+    }
+
+    pub fn is_energized(&self, (row, col): (usize, usize)) -> bool {
+        self.data[row][col] != 0
+    }
+
+    pub fn energized(&self) -> usize {
+        self.data
+            .iter()
+            .flatten()
+            .filter(|&&tile| tile != 0)
+            .count()
+    }
+
+    /// Draws the energized tiles as `#` and the rest as `.`
+    pub fn render_energized(&self) -> String {
+        let mut out = String::new();
+        for (ridx, row) in self.data.iter().enumerate() {
+            for cidx in 0..row.len() {
+                out.push(match self.is_energized((ridx, cidx)) {
+                    true => '#',
+                    false => '.',
+                });
+            }
+            out.push('\n');
+        }
+
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::contraption::Dir;
+
+    use super::Beams;
+
+    #[test]
+    fn insert_beams() {
+        let mut beams = Beams::new(2, 2);
+        assert!(beams.insert((0, 1), Dir::Up));
+        assert!(beams.insert((0, 1), Dir::Left));
+        assert!(!beams.insert((0, 1), Dir::Up));
+
+        assert_eq!(
+            beams.dirs((0, 1)).collect::<Vec<_>>(),
+            vec![Dir::Left, Dir::Up]
+        );
+        assert_eq!(beams.energized(), 1);
+        assert_eq!(beams.render_energized(), ".#\n..\n");
+    }
+}
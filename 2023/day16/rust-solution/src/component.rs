@@ -0,0 +1,249 @@
+use std::{collections::HashMap, fmt::Debug, sync::Arc};
+
+use smallvec::{smallvec, SmallVec};
+
+use crate::contraption::Dir;
+
+const EMPTY: char = '.';
+const LEFT_TILTED_MIRROR: char = '\\';
+const RIGHT_TILTED_MIRROR: char = '/';
+const VERTICAL_SPLITTER: char = '|';
+const HORIZONTAL_SPLITTER: char = '-';
+const ABSORBER: char = '#';
+const LEFT_TILTED_BEAM_SPLITTER: char = '╲';
+const RIGHT_TILTED_BEAM_SPLITTER: char = '╱';
+// the one way mirrors are drawn as the corner their reflective side faces
+const UPPER_LEFT_MIRROR: char = '◸';
+const UPPER_RIGHT_MIRROR: char = '◹';
+const LOWER_LEFT_MIRROR: char = '◺';
+const LOWER_RIGHT_MIRROR: char = '◿';
+
+/// Anything that can sit on a tile of the contraption
+pub trait Component: Debug + Send + Sync {
+    /// The directions the beam leaves in, after entering the tile going `dir`
+    fn deflect(&self, dir: Dir) -> SmallVec<[Dir; 2]>;
+
+    /// Whether every beam goes straight through, like through an empty tile
+    fn is_transparent(&self) -> bool {
+        Dir::ALL
+            .into_iter()
+            .all(|dir| self.deflect(dir).as_slice() == [dir])
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Empty;
+
+impl Component for Empty {
+    fn deflect(&self, dir: Dir) -> SmallVec<[Dir; 2]> {
+        smallvec![dir]
+    }
+}
+
+/// The way a mirror leans, `\` is tilted left and `/` is tilted right
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tilt {
+    Left,
+    Right,
+}
+
+impl Tilt {
+    pub fn reflect(self, dir: Dir) -> Dir {
+        match (self, dir) {
+            (Self::Left, Dir::Down) | (Self::Right, Dir::Up) => Dir::Right,
+            (Self::Left, Dir::Left) | (Self::Right, Dir::Right) => Dir::Up,
+            (Self::Left, Dir::Right) | (Self::Right, Dir::Left) => Dir::Down,
+            (Self::Left, Dir::Up) | (Self::Right, Dir::Down) => Dir::Left,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Mirror(pub Tilt);
+
+impl Component for Mirror {
+    fn deflect(&self, dir: Dir) -> SmallVec<[Dir; 2]> {
+        smallvec![self.0.reflect(dir)]
+    }
+}
+
+/// Splits the beams hitting its flat side, and lets through the ones hitting its pointy end
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Splitter {
+    pub vertical: bool,
+}
+
+impl Component for Splitter {
+    fn deflect(&self, dir: Dir) -> SmallVec<[Dir; 2]> {
+        match (self.vertical, dir) {
+            (true, Dir::Left | Dir::Right) => smallvec![Dir::Up, Dir::Down],
+            (false, Dir::Up | Dir::Down) => smallvec![Dir::Left, Dir::Right],
+            _ => smallvec![dir],
+        }
+    }
+}
+
+/// Stops every beam
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Absorber;
+
+impl Component for Absorber {
+    fn deflect(&self, _: Dir) -> SmallVec<[Dir; 2]> {
+        smallvec![]
+    }
+}
+
+/// A mirror that only reflects on the side facing `facing`,
+/// the beams hitting its back go straight through.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OneWayMirror {
+    pub tilt: Tilt,
+    pub facing: Dir,
+}
+
+impl Component for OneWayMirror {
+    fn deflect(&self, dir: Dir) -> SmallVec<[Dir; 2]> {
+        // the reflective side faces both `facing` and the direction
+        // a beam coming back along `facing` gets reflected away from
+        let front = dir.opposite() == self.facing || dir == self.tilt.reflect(self.facing);
+        match front {
+            true => smallvec![self.tilt.reflect(dir)],
+            false => smallvec![dir],
+        }
+    }
+}
+
+/// A half silvered mirror, every beam both goes through and gets reflected
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BeamSplitter(pub Tilt);
+
+impl Component for BeamSplitter {
+    fn deflect(&self, dir: Dir) -> SmallVec<[Dir; 2]> {
+        smallvec![dir, self.0.reflect(dir)]
+    }
+}
+
+/// The components the characters of the contraption stand for
+#[derive(Debug, Clone)]
+pub struct SymbolTable {
+    components: HashMap<char, Arc<dyn Component>>,
+}
+
+impl SymbolTable {
+    /// A table without any symbols
+    pub fn empty() -> Self {
+        Self {
+            components: HashMap::new(),
+        }
+    }
+
+    /// Binds the symbol to the component, replacing the previous one
+    pub fn register(&mut self, symbol: char, component: impl Component + 'static) {
+        self.components.insert(symbol, Arc::new(component));
+    }
+
+    pub fn get(&self, symbol: char) -> Option<&Arc<dyn Component>> {
+        self.components.get(&symbol)
+    }
+}
+
+/// The components of the puzzle, along with absorbers,
+/// beam splitters and one way mirrors for our own variants
+impl Default for SymbolTable {
+    fn default() -> Self {
+        let mut table = Self::empty();
+        table.register(EMPTY, Empty);
+        table.register(LEFT_TILTED_MIRROR, Mirror(Tilt::Left));
+        table.register(RIGHT_TILTED_MIRROR, Mirror(Tilt::Right));
+        table.register(VERTICAL_SPLITTER, Splitter { vertical: true });
+        table.register(HORIZONTAL_SPLITTER, Splitter { vertical: false });
+
+        table.register(ABSORBER, Absorber);
+        table.register(LEFT_TILTED_BEAM_SPLITTER, BeamSplitter(Tilt::Left));
+        table.register(RIGHT_TILTED_BEAM_SPLITTER, BeamSplitter(Tilt::Right));
+        for (symbol, tilt, facing) in [
+            (UPPER_LEFT_MIRROR, Tilt::Right, Dir::Up),
+            (UPPER_RIGHT_MIRROR, Tilt::Left, Dir::Up),
+            (LOWER_LEFT_MIRROR, Tilt::Left, Dir::Down),
+            (LOWER_RIGHT_MIRROR, Tilt::Right, Dir::Down),
+        ] {
+            table.register(symbol, OneWayMirror { tilt, facing });
+        }
+        table
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::contraption::Dir;
+
+    use super::{
+        Absorber, BeamSplitter, Component, Empty, Mirror, OneWayMirror, Splitter, SymbolTable, Tilt,
+    };
+
+    #[test]
+    fn mirrors() {
+        assert_eq!(
+            Mirror(Tilt::Left).deflect(Dir::Right).as_slice(),
+            [Dir::Down]
+        );
+        assert_eq!(Mirror(Tilt::Left).deflect(Dir::Up).as_slice(), [Dir::Left]);
+        assert_eq!(
+            Mirror(Tilt::Right).deflect(Dir::Right).as_slice(),
+            [Dir::Up]
+        );
+        assert_eq!(
+            Mirror(Tilt::Right).deflect(Dir::Left).as_slice(),
+            [Dir::Down]
+        );
+    }
+
+    #[test]
+    fn splitters() {
+        let vertical = Splitter { vertical: true };
+        assert_eq!(vertical.deflect(Dir::Left).as_slice(), [Dir::Up, Dir::Down]);
+        assert_eq!(vertical.deflect(Dir::Up).as_slice(), [Dir::Up]);
+        assert!(!vertical.is_transparent());
+        assert!(Empty.is_transparent());
+
+        assert!(Absorber.deflect(Dir::Down).is_empty());
+        assert_eq!(
+            BeamSplitter(Tilt::Right).deflect(Dir::Right).as_slice(),
+            [Dir::Right, Dir::Up]
+        );
+    }
+
+    #[test]
+    fn one_way_mirror() {
+        // `\` reflecting on its lower left side
+        let mirror = OneWayMirror {
+            tilt: Tilt::Left,
+            facing: Dir::Left,
+        };
+        assert_eq!(mirror.deflect(Dir::Right).as_slice(), [Dir::Down]);
+        assert_eq!(mirror.deflect(Dir::Up).as_slice(), [Dir::Left]);
+        assert_eq!(mirror.deflect(Dir::Left).as_slice(), [Dir::Left]);
+        assert_eq!(mirror.deflect(Dir::Down).as_slice(), [Dir::Down]);
+    }
+
+    #[test]
+    fn default_table() {
+        let table = SymbolTable::default();
+        assert!(table.get('.').unwrap().is_transparent());
+        assert_eq!(
+            table.get('-').unwrap().deflect(Dir::Up).as_slice(),
+            [Dir::Left, Dir::Right]
+        );
+        assert!(table.get('#').unwrap().deflect(Dir::Up).is_empty());
+        assert!(table.get('x').is_none());
+
+        // the one way mirrors reflect the beams hitting the side they are named after
+        let mirror = table.get('◸').unwrap();
+        assert_eq!(mirror.deflect(Dir::Down).as_slice(), [Dir::Left]);
+        assert_eq!(mirror.deflect(Dir::Left).as_slice(), [Dir::Left]);
+        let mirror = table.get('◺').unwrap();
+        assert_eq!(mirror.deflect(Dir::Right).as_slice(), [Dir::Down]);
+        assert_eq!(mirror.deflect(Dir::Up).as_slice(), [Dir::Left]);
+        assert_eq!(mirror.deflect(Dir::Down).as_slice(), [Dir::Down]);
+    }
+}
@@ -1,10 +1,16 @@
-use std::{collections::HashSet, str::FromStr};
+use std::{
+    fmt::{self, Display},
+    str::FromStr,
+    sync::Arc,
+};
 
-const EMPTY: char = '.';
-const LEFT_TILTED_MIRROR: char = '\\';
-const RIGHT_TILTED_MIRROR: char = '/';
-const VERTICAL_SPLITTER: char = '|';
-const HORIZONTAL_SPLITTER: char = '-';
+use smallvec::SmallVec;
+
+use crate::{
+    beams::Beams,
+    component::{Component, SymbolTable},
+    segments::{self, Entry},
+};
 
 #[derive(thiserror::Error, Debug)]
 pub enum GridErr {
@@ -18,43 +24,6 @@ pub enum GridErr {
     UnknownSpace(char),
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum Space {
-    Empty,
-    LeftTiltedMirror,
-    RightTiltedMirror,
-    VerticalSplitter,
-    HorizontalSplitter,
-}
-
-impl Space {
-    fn calculate_next_dir(&self, dir: Dir) -> [Option<Dir>; 2] {
-        match (self, dir) {
-            (Self::LeftTiltedMirror, _) => [
-                Some(match dir {
-                    Dir::Down => Dir::Right,
-                    Dir::Left => Dir::Up,
-                    Dir::Right => Dir::Down,
-                    Dir::Up => Dir::Left,
-                }),
-                None,
-            ],
-            (Self::RightTiltedMirror, _) => [
-                Some(match dir {
-                    Dir::Down => Dir::Left,
-                    Dir::Left => Dir::Down,
-                    Dir::Right => Dir::Up,
-                    Dir::Up => Dir::Right,
-                }),
-                None,
-            ],
-            (Self::VerticalSplitter, Dir::Right | Dir::Left) => [Some(Dir::Up), Some(Dir::Down)],
-            (Self::HorizontalSplitter, Dir::Down | Dir::Up) => [Some(Dir::Left), Some(Dir::Right)],
-            _ => [Some(dir), None],
-        }
-    }
-}
-
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Dir {
     Left,
@@ -64,6 +33,26 @@ pub enum Dir {
 }
 
 impl Dir {
+    pub const ALL: [Self; 4] = [Self::Left, Self::Down, Self::Right, Self::Up];
+
+    pub fn arrow(self) -> char {
+        match self {
+            Self::Left => '<',
+            Self::Down => 'v',
+            Self::Right => '>',
+            Self::Up => '^',
+        }
+    }
+
+    pub fn opposite(self) -> Self {
+        match self {
+            Self::Left => Self::Right,
+            Self::Down => Self::Up,
+            Self::Right => Self::Left,
+            Self::Up => Self::Down,
+        }
+    }
+
     fn apply(self, pos: (usize, usize)) -> Option<(usize, usize)> {
         match self {
             Self::Left => {
@@ -86,38 +75,122 @@ impl Dir {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone)]
+struct Tile {
+    symbol: char,
+    component: Arc<dyn Component>,
+}
+
+#[derive(Debug, Clone)]
 pub struct Grid {
-    data: Vec<Vec<Space>>,
+    data: Vec<Vec<Tile>>,
 }
 
 impl Grid {
+    /// Parses the contraption, looking up the components of every character in the table
+    pub fn parse(s: &str, table: &SymbolTable) -> Result<Self, GridErr> {
+        let data = s
+            .trim()
+            .lines()
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty())
+            .map(|line| {
+                line.chars()
+                    .map(|ch| {
+                        let component = table.get(ch).ok_or(GridErr::UnknownSpace(ch))?;
+                        Ok(Tile {
+                            symbol: ch,
+                            component: component.clone(),
+                        })
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if data.is_empty() {
+            return Err(GridErr::Empty);
+        }
+
+        let rlen = data[0].len();
+        if data.iter().any(|line| line.len() != rlen) {
+            return Err(GridErr::UnEven);
+        }
+
+        Ok(Self { data })
+    }
+
     pub fn calculate_energized(&self, start: (usize, usize), dir: Dir) -> usize {
-        let mut frontier = vec![(dir, start)];
-        let mut visited_spaces: HashSet<(usize, usize)> = Default::default();
-        let mut visited: HashSet<(Dir, (usize, usize))> = Default::default();
+        self.energized_map(start, dir).energized()
+    }
+
+    /// Traces the beam, and records the directions it passed through every tile
+    pub fn energized_map(&self, start: (usize, usize), dir: Dir) -> Beams {
+        let (rlen, clen) = self.len();
+        let mut beams = Beams::new(rlen, clen);
 
+        let mut frontier = vec![(dir, start)];
         while let Some((dir, (row, col))) = frontier.pop() {
-            if row >= self.data.len() || col >= self.data[0].len() {
+            if row >= rlen || col >= clen {
                 continue;
             }
-            visited_spaces.insert((row, col));
 
-            if !visited.insert((dir, (row, col))) {
+            if !beams.insert((row, col), dir) {
                 continue;
             }
 
-            let space = self.data[row][col];
             frontier.extend(
-                space
-                    .calculate_next_dir(dir)
+                self.deflect((row, col), dir)
                     .into_iter()
-                    .flatten()
                     .filter_map(|dir| dir.apply((row, col)).map(|pos| (dir, pos))),
             )
         }
 
-        visited_spaces.len()
+        beams
+    }
+
+    /// The directions a beam leaves the tile in, after entering it in `dir`
+    pub fn deflect(&self, pos: (usize, usize), dir: Dir) -> SmallVec<[Dir; 2]> {
+        self.data[pos.0][pos.1].component.deflect(dir)
+    }
+
+    /// Moves one tile in the direction, if it doesn't leave the grid
+    pub fn step(&self, pos: (usize, usize), dir: Dir) -> Option<(usize, usize)> {
+        let (rlen, clen) = self.len();
+        dir.apply(pos)
+            .filter(|&(row, col)| row < rlen && col < clen)
+    }
+
+    /// Every tile on the edge of the grid, with the direction that enters the grid
+    pub fn edge_entries(&self) -> Vec<((usize, usize), Dir)> {
+        let (rlen, clen) = self.len();
+        (0..rlen)
+            .flat_map(|ridx| [((ridx, 0), Dir::Right), ((ridx, clen - 1), Dir::Left)])
+            .chain((0..clen).flat_map(|cidx| [((0, cidx), Dir::Down), ((rlen - 1, cidx), Dir::Up)]))
+            .collect()
+    }
+
+    /// Finds the edge entry that energizes the most tiles
+    pub fn best_entry(&self) -> Entry {
+        segments::best_entry(self)
+    }
+
+    /// Draws the contraption with the beams passing through the transparent tiles,
+    /// an arrow for a single beam, or the number of beams when there are more.
+    pub fn render_beams(&self, beams: &Beams) -> String {
+        let mut out = String::new();
+        for (ridx, row) in self.data.iter().enumerate() {
+            for (cidx, tile) in row.iter().enumerate() {
+                let dirs = beams.dirs((ridx, cidx)).collect::<Vec<_>>();
+                out.push(match (tile.component.is_transparent(), dirs.as_slice()) {
+                    (true, [dir]) => dir.arrow(),
+                    (true, [_, ..]) => char::from_digit(dirs.len() as u32, 10).unwrap(),
+                    _ => tile.symbol,
+                });
+            }
+            out.push('\n');
+        }
+
+        out
     }
 
     pub fn len(&self) -> (usize, usize) {
@@ -125,77 +198,52 @@ impl Grid {
     }
 }
 
+/// Parses the contraption using the puzzle's symbols
 impl FromStr for Grid {
     type Err = GridErr;
+
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let data = s
-            .trim()
-            .lines()
-            .map(|line| line.trim())
-            .filter(|line| !line.is_empty())
-            .map(|line| {
-                line.chars()
-                    .map(|ch| {
-                        Ok(match ch {
-                            EMPTY => Space::Empty,
-                            LEFT_TILTED_MIRROR => Space::LeftTiltedMirror,
-                            RIGHT_TILTED_MIRROR => Space::RightTiltedMirror,
-                            VERTICAL_SPLITTER => Space::VerticalSplitter,
-                            HORIZONTAL_SPLITTER => Space::HorizontalSplitter,
-                            _ => return Err(GridErr::UnknownSpace(ch)),
-                        })
-                    })
-                    .collect::<Result<Vec<_>, _>>()
-            })
-            .collect::<Result<Vec<_>, _>>()?;
+        Self::parse(s, &SymbolTable::default())
+    }
+}
 
-        if data.is_empty() {
-            return Err(GridErr::Empty);
+impl Display for Grid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in &self.data {
+            writeln!(
+                f,
+                "{}",
+                row.iter().map(|tile| tile.symbol).collect::<String>()
+            )?;
         }
 
-        let rlen = data[0].len();
-        if data.iter().any(|line| line.len() != rlen) {
-            return Err(GridErr::UnEven);
-        }
-
-        Ok(Self { data })
+        Ok(())
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{Dir, Grid, Space};
+    use crate::component::{Absorber, BeamSplitter, OneWayMirror, SymbolTable, Tilt};
+
+    use super::{Dir, Grid, GridErr};
 
     #[test]
     fn parse_data() {
         let input = r#".|...\"#;
-        let expected_output = Grid {
-            data: vec![vec![
-                Space::Empty,
-                Space::VerticalSplitter,
-                Space::Empty,
-                Space::Empty,
-                Space::Empty,
-                Space::LeftTiltedMirror,
-            ]],
-        };
 
         let output: Grid = input.parse().unwrap();
-        assert_eq!(output, expected_output);
+        assert_eq!(output.len(), (1, 6));
+        assert_eq!(output.to_string(), ".|...\\\n");
+        assert!(matches!(
+            "..x".parse::<Grid>(),
+            Err(GridErr::UnknownSpace('x'))
+        ));
     }
 
     #[test]
     fn calculate_next_dir() {
-        let space = Space::LeftTiltedMirror;
-        let dir = Dir::Right;
-        assert_eq!(
-            space
-                .calculate_next_dir(dir)
-                .into_iter()
-                .flatten()
-                .collect::<Vec<_>>(),
-            &[Dir::Down]
-        );
+        let grid: Grid = r#"\"#.parse().unwrap();
+        assert_eq!(grid.deflect((0, 0), Dir::Right).as_slice(), &[Dir::Down]);
     }
 
     #[test]
@@ -203,4 +251,95 @@ mod tests {
         let input: Grid = r#".|...\...."#.parse().unwrap();
         assert_eq!(input.calculate_energized((0, 0), Dir::Right), 2);
     }
+
+    const INPUT: &str = r#".|...\....
+|.-.\.....
+.....|-...
+........|.
+..........
+.........\
+..../.\\..
+.-.-/..|..
+.|....-|.\
+..//.|...."#;
+
+    #[test]
+    fn render_beams() {
+        let grid: Grid = INPUT.parse().unwrap();
+        let beams = grid.energized_map((0, 0), Dir::Right);
+
+        assert_eq!(
+            grid.render_beams(&beams),
+            r#">|<<<\....
+|v-.\^....
+.v...|->>>
+.v...v^.|.
+.v...v^...
+.v...v^..\
+.v../2\\..
+<->-/vv|..
+.|<<<2-|.\
+.v//.|.v..
+"#
+        );
+
+        assert_eq!(
+            beams.render_energized(),
+            r#"######....
+.#...#....
+.#...#####
+.#...##...
+.#...##...
+.#...##...
+.#..####..
+########..
+.#######..
+.#...#.#..
+"#
+        );
+    }
+
+    #[test]
+    fn custom_components() {
+        let mut table = SymbolTable::default();
+        table.register('#', Absorber);
+        table.register('%', BeamSplitter(Tilt::Left));
+        table.register(
+            '>',
+            OneWayMirror {
+                tilt: Tilt::Right,
+                facing: Dir::Down,
+            },
+        );
+
+        let grid = Grid::parse(
+            r#"..%..#
+..>...
+......"#,
+            &table,
+        )
+        .unwrap();
+
+        // the beam splitter sends half the beam down, the one way mirror
+        // lets it through from the back, and the absorber stops the rest
+        let beams = grid.energized_map((0, 0), Dir::Right);
+        assert_eq!(
+            grid.render_beams(&beams),
+            r#">>%>>#
+..>...
+..v...
+"#
+        );
+        assert_eq!(beams.energized(), 8);
+
+        // coming back up, the beam hits the mirror's front and turns right
+        let beams = grid.energized_map((2, 2), Dir::Up);
+        assert_eq!(
+            grid.render_beams(&beams),
+            r#"..%..#
+..>>>>
+..^...
+"#
+        );
+    }
 }
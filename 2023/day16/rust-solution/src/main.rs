@@ -1,7 +1,9 @@
 use contraption::{Grid, GridErr};
-use rayon::iter::{ParallelBridge, ParallelIterator};
 
+mod beams;
+mod component;
 mod contraption;
+mod segments;
 
 fn part_1(input: &str) -> Result<usize, GridErr> {
     let grid: Grid = input.parse()?;
@@ -10,30 +12,22 @@ fn part_1(input: &str) -> Result<usize, GridErr> {
 
 fn part_2(input: &str) -> Result<usize, GridErr> {
     let grid: Grid = input.parse()?;
-
-    let (rlen, clen) = grid.len();
-    let all_possible_starts = (0..rlen)
-        .flat_map(|ridx| {
-            [
-                ((ridx, 0), contraption::Dir::Right),
-                ((ridx, clen - 1), contraption::Dir::Left),
-            ]
-        })
-        .chain((0..clen).flat_map(|cidx| {
-            [
-                ((0, cidx), contraption::Dir::Down),
-                ((rlen - 1, cidx), contraption::Dir::Up),
-            ]
-        }));
-
-    Ok(all_possible_starts
-        .par_bridge()
-        .map(|(pos, dir)| grid.calculate_energized(pos, dir))
-        .max()
-        .unwrap())
+    let best = grid.best_entry();
+    tracing::debug!(
+        "beams from the best entry {:?}:\n{}",
+        best,
+        grid.render_beams(&grid.energized_map(best.start, best.dir))
+    );
+    tracing::trace!(
+        "tiles energized from the best entry:\n{}",
+        grid.energized_map(best.start, best.dir).render_energized()
+    );
+
+    Ok(best.energized)
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
+    tracing_subscriber::fmt::init();
     let input = rust_shared_utils::read_puzzle()?;
     println!("part 1: {}", part_1(&input)?);
     println!("part 2: {}", part_2(&input)?);
@@ -0,0 +1,352 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::contraption::{Dir, Grid};
+
+/// A place to shine the beam from, and how many tiles it energizes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Entry {
+    pub start: (usize, usize),
+    pub dir: Dir,
+    pub energized: usize,
+}
+
+/// A fixed size set of tiles
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct TileSet {
+    bits: Vec<u64>,
+}
+
+impl TileSet {
+    fn new(len: usize) -> Self {
+        Self {
+            bits: vec![0; len.div_ceil(64)],
+        }
+    }
+
+    fn insert(&mut self, idx: usize) {
+        self.bits[idx / 64] |= 1 << (idx % 64);
+    }
+
+    fn union_with(&mut self, other: &Self) {
+        for (bits, other) in self.bits.iter_mut().zip(&other.bits) {
+            *bits |= other;
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.bits
+            .iter()
+            .map(|bits| bits.count_ones() as usize)
+            .sum()
+    }
+}
+
+/// A splitter, and the direction the beam hit it in
+type Split = ((usize, usize), Dir);
+
+/// The tiles a beam passes until it gets split, leaves the grid or loops back on itself
+struct Segment {
+    tiles: TileSet,
+    split: Option<Split>,
+}
+
+fn trace(grid: &Grid, start: (usize, usize), dir: Dir) -> Segment {
+    let (rlen, clen) = grid.len();
+    let mut tiles = TileSet::new(rlen * clen);
+    let mut visited = HashSet::new();
+
+    let (mut pos, mut dir) = (start, dir);
+    while visited.insert((pos, dir)) {
+        tiles.insert(pos.0 * clen + pos.1);
+
+        match grid.deflect(pos, dir).as_slice() {
+            [next] => dir = *next,
+            [] => break,
+            _ => {
+                return Segment {
+                    tiles,
+                    split: Some((pos, dir)),
+                }
+            }
+        }
+
+        match grid.step(pos, dir) {
+            Some(next) => pos = next,
+            None => break,
+        }
+    }
+
+    Segment { tiles, split: None }
+}
+
+/// The splitters hit by the beams, each one owns the tiles of the beams leaving it
+struct SplitGraph {
+    tiles: Vec<TileSet>,
+    edges: Vec<Vec<usize>>,
+    indices: HashMap<Split, usize>,
+}
+
+impl SplitGraph {
+    fn new(grid: &Grid) -> Self {
+        let mut graph = Self {
+            tiles: vec![],
+            edges: vec![],
+            indices: HashMap::new(),
+        };
+
+        for (start, dir) in grid.edge_entries() {
+            if let Some(split) = trace(grid, start, dir).split {
+                graph.add(grid, split);
+            }
+        }
+
+        graph
+    }
+
+    fn add(&mut self, grid: &Grid, split: Split) -> usize {
+        let mut pending = vec![];
+        let root = self.node(grid, split, &mut pending);
+
+        while let Some((idx, (pos, dir))) = pending.pop() {
+            for next in grid.deflect(pos, dir) {
+                let Some(start) = grid.step(pos, next) else {
+                    continue;
+                };
+
+                let segment = trace(grid, start, next);
+                self.tiles[idx].union_with(&segment.tiles);
+                if let Some(split) = segment.split {
+                    let next = self.node(grid, split, &mut pending);
+                    self.edges[idx].push(next);
+                }
+            }
+        }
+
+        root
+    }
+
+    // the index of the splitter, new splitters are queued to trace the beams leaving them
+    fn node(&mut self, grid: &Grid, split: Split, pending: &mut Vec<(usize, Split)>) -> usize {
+        if let Some(&idx) = self.indices.get(&split) {
+            return idx;
+        }
+
+        let idx = self.tiles.len();
+        let (rlen, clen) = grid.len();
+        let (pos, _) = split;
+        self.indices.insert(split, idx);
+        self.tiles.push(TileSet::new(rlen * clen));
+        self.edges.push(vec![]);
+        self.tiles[idx].insert(pos.0 * clen + pos.1);
+
+        pending.push((idx, split));
+        idx
+    }
+
+    /// Every tile energized once the beam reaches each splitter,
+    /// splitters in the same cycle share the same tiles.
+    fn reachable_tiles(&self) -> Vec<TileSet> {
+        let components = strongly_connected(&self.edges);
+
+        let mut component_of = vec![0; self.edges.len()];
+        for (cidx, component) in components.iter().enumerate() {
+            for &idx in component {
+                component_of[idx] = cidx;
+            }
+        }
+
+        // the components come out in reverse topological order,
+        // so every successor is complete before it's needed
+        let mut reachable: Vec<TileSet> = Vec::with_capacity(components.len());
+        for (cidx, component) in components.iter().enumerate() {
+            let mut tiles = self.tiles[component[0]].clone();
+            for &idx in component {
+                tiles.union_with(&self.tiles[idx]);
+                for &next in &self.edges[idx] {
+                    if component_of[next] != cidx {
+                        tiles.union_with(&reachable[component_of[next]]);
+                    }
+                }
+            }
+            reachable.push(tiles);
+        }
+
+        component_of
+            .into_iter()
+            .map(|cidx| reachable[cidx].clone())
+            .collect()
+    }
+}
+
+/// Tarjan's algorithm, the components are returned in reverse topological order
+fn strongly_connected(edges: &[Vec<usize>]) -> Vec<Vec<usize>> {
+    struct Tarjan<'a> {
+        edges: &'a [Vec<usize>],
+        index: Vec<Option<usize>>,
+        low: Vec<usize>,
+        on_stack: Vec<bool>,
+        stack: Vec<usize>,
+        next: usize,
+        components: Vec<Vec<usize>>,
+    }
+
+    impl Tarjan<'_> {
+        fn enter(&mut self, idx: usize) {
+            self.index[idx] = Some(self.next);
+            self.low[idx] = self.next;
+            self.next += 1;
+            self.stack.push(idx);
+            self.on_stack[idx] = true;
+        }
+
+        // the calls are kept on an explicit stack, every frame
+        // holds the node and the next of its edges to follow
+        fn visit(&mut self, root: usize) {
+            self.enter(root);
+            let mut calls = vec![(root, 0)];
+
+            while let Some(&(idx, edge)) = calls.last() {
+                if let Some(&next) = self.edges[idx].get(edge) {
+                    calls.last_mut().unwrap().1 += 1;
+                    match self.index[next] {
+                        None => {
+                            self.enter(next);
+                            calls.push((next, 0));
+                        }
+                        Some(index) if self.on_stack[next] => {
+                            self.low[idx] = self.low[idx].min(index)
+                        }
+                        Some(_) => {}
+                    }
+                    continue;
+                }
+
+                calls.pop();
+                if let Some(&(parent, _)) = calls.last() {
+                    self.low[parent] = self.low[parent].min(self.low[idx]);
+                }
+
+                if Some(self.low[idx]) == self.index[idx] {
+                    let mut component = vec![];
+                    while let Some(top) = self.stack.pop() {
+                        self.on_stack[top] = false;
+                        component.push(top);
+                        if top == idx {
+                            break;
+                        }
+                    }
+                    self.components.push(component);
+                }
+            }
+        }
+    }
+
+    let len = edges.len();
+    let mut tarjan = Tarjan {
+        edges,
+        index: vec![None; len],
+        low: vec![0; len],
+        on_stack: vec![false; len],
+        stack: vec![],
+        next: 0,
+        components: vec![],
+    };
+
+    for idx in 0..len {
+        if tarjan.index[idx].is_none() {
+            tarjan.visit(idx);
+        }
+    }
+
+    tarjan.components
+}
+
+/// Tries every entry on the edges of the grid, the beams are only traced
+/// once between two splitters, and shared by every entry that reaches them.
+pub fn best_entry(grid: &Grid) -> Entry {
+    let graph = SplitGraph::new(grid);
+    let reachable = graph.reachable_tiles();
+
+    grid.edge_entries()
+        .into_iter()
+        .map(|(start, dir)| {
+            let segment = trace(grid, start, dir);
+            let mut tiles = segment.tiles;
+            if let Some(split) = segment.split {
+                tiles.union_with(&reachable[graph.indices[&split]]);
+            }
+
+            Entry {
+                start,
+                dir,
+                energized: tiles.len(),
+            }
+        })
+        .max_by_key(|entry| entry.energized)
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::contraption::{Dir, Grid};
+
+    use super::{best_entry, strongly_connected, trace, Entry, SplitGraph};
+
+    #[test]
+    fn components() {
+        let components = strongly_connected(&[vec![1], vec![2], vec![1, 3], vec![]]);
+        assert_eq!(components, vec![vec![3], vec![2, 1], vec![0]]);
+    }
+
+    #[test]
+    fn long_chain_components() {
+        // deep enough to overflow the stack if the visit recursed
+        let len = 200_000;
+        let mut edges = (1..len).map(|next| vec![next]).collect::<Vec<_>>();
+        edges.push(vec![0]);
+
+        let components = strongly_connected(&edges);
+        assert_eq!(components.len(), 1);
+        assert_eq!(components[0].len(), len);
+
+        edges[len - 1].clear();
+        assert_eq!(strongly_connected(&edges).len(), len);
+    }
+
+    #[test]
+    fn best_entry_matches_brute_force() {
+        let grid: Grid = r#".|...\....
+|.-.\.....
+.....|-...
+........|.
+..........
+.........\
+..../.\\..
+.-.-/..|..
+.|....-|.\
+..//.|...."#
+            .parse()
+            .unwrap();
+
+        assert_eq!(
+            best_entry(&grid),
+            Entry {
+                start: (0, 3),
+                dir: Dir::Down,
+                energized: 51
+            }
+        );
+
+        let graph = SplitGraph::new(&grid);
+        let reachable = graph.reachable_tiles();
+        for (start, dir) in grid.edge_entries() {
+            let beams = grid.energized_map(start, dir);
+            let segment = trace(&grid, start, dir);
+            let mut tiles = segment.tiles;
+            if let Some(split) = segment.split {
+                tiles.union_with(&reachable[graph.indices[&split]]);
+            }
+            assert_eq!(tiles.len(), beams.energized(), "{:?} {:?}", start, dir);
+        }
+    }
+}
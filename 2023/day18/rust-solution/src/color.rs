@@ -0,0 +1,70 @@
+use std::{
+    fmt::{self, Display},
+    str::FromStr,
+};
+
+#[derive(thiserror::Error, Debug)]
+pub enum ColorErr {
+    #[error("unknown color format ({0})")]
+    UnknownFormat(String),
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rgb {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Rgb {
+    pub const fn new(r: u8, g: u8, b: u8) -> Self {
+        Self { r, g, b }
+    }
+
+    /// Wraps the text with an ANSI escape code that paints it in the color
+    pub fn paint(self, text: &str) -> String {
+        format!("\x1b[38;2;{};{};{}m{}\x1b[0m", self.r, self.g, self.b, text)
+    }
+}
+
+impl FromStr for Rgb {
+    type Err = ColorErr;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let hex = s
+            .trim()
+            .strip_prefix('#')
+            .filter(|hex| hex.len() == 6 && hex.is_ascii())
+            .ok_or_else(|| ColorErr::UnknownFormat(s.into()))?;
+
+        let channel = |idx: usize| {
+            u8::from_str_radix(&hex[idx..idx + 2], 16)
+                .map_err(|_| ColorErr::UnknownFormat(s.into()))
+        };
+
+        Ok(Self::new(channel(0)?, channel(2)?, channel(4)?))
+    }
+}
+
+/// Displays the color in hex, as it appears in the dig plan
+impl Display for Rgb {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Rgb;
+
+    #[test]
+    fn parse_rgb() {
+        let color: Rgb = "#70c710".parse().unwrap();
+        assert_eq!(color, Rgb::new(0x70, 0xc7, 0x10));
+        assert_eq!(color.to_string(), "#70c710");
+
+        assert!("70c710".parse::<Rgb>().is_err());
+        assert!("#70c71".parse::<Rgb>().is_err());
+        assert!("#70c71g".parse::<Rgb>().is_err());
+    }
+}
@@ -1,6 +1,9 @@
-use std::str::FromStr;
+use std::{collections::HashMap, str::FromStr};
 
-use crate::position::{Direction, Position};
+use crate::{
+    color::{ColorErr, Rgb},
+    position::{Direction, Position},
+};
 
 #[derive(thiserror::Error, Debug)]
 pub enum DiggerErr {
@@ -24,12 +27,29 @@ pub enum DiggerErr {
 
     #[error("unknown direction in rgb format ({0})")]
     UnknownDirRgb(char),
+
+    #[error(transparent)]
+    BadColor(#[from] ColorErr),
+
+    #[error("the trench doesn't return to its start, it ends at ({0}, {1})")]
+    OpenPath(isize, isize),
+
+    #[error("instructions {0} and {1} cross each other")]
+    SelfIntersection(usize, usize),
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 struct Instruction {
     steps: usize,
     direction: Direction,
+    color: Rgb,
+}
+
+fn parse_color(s: &str) -> Result<&str, DiggerErr> {
+    s.trim()
+        .strip_prefix('(')
+        .and_then(|color| color.strip_suffix(')'))
+        .ok_or_else(|| DiggerErr::UnknownColor(s.into()))
 }
 
 impl Instruction {
@@ -39,24 +59,27 @@ impl Instruction {
             return Err(DiggerErr::UnknownFormat(s.into()));
         }
 
-        let color = parts[2]
-            .trim()
-            .strip_prefix('(')
-            .and_then(|color| color.strip_prefix('#'))
-            .and_then(|color| color.strip_suffix(')'))
-            .ok_or_else(|| DiggerErr::UnknownColor(parts[3].into()))?;
-        if color.as_bytes().len() != 6 {
-            return Err(DiggerErr::BadRgb(color.into()));
+        let hex = parse_color(parts[2])?;
+        let digits = hex
+            .strip_prefix('#')
+            .ok_or_else(|| DiggerErr::UnknownColor(hex.into()))?;
+        if digits.len() != 6 {
+            return Err(DiggerErr::BadRgb(digits.into()));
         }
+        let color = hex.parse()?;
 
-        let steps = &color[0..5];
+        let steps = &digits[0..5];
         let steps =
             usize::from_str_radix(steps, 16).map_err(|_| DiggerErr::BadStepsRgb(steps.into()))?;
-        let direction = color.chars().nth(5).unwrap();
+        let direction = digits.chars().nth(5).unwrap();
         let direction =
             Direction::from_digit(direction).ok_or(DiggerErr::UnknownDirRgb(direction))?;
 
-        Ok(Self { steps, direction })
+        Ok(Self {
+            steps,
+            direction,
+            color,
+        })
     }
 }
 
@@ -66,23 +89,179 @@ pub struct Plan {
 }
 
 impl Plan {
-    pub fn calculate_area(&self) -> usize {
-        let mut dots = Vec::new();
-        let mut perimeter = 0;
-
+    /// The corners of the trench in digging order, starting at the origin
+    pub fn vertices(&self) -> Vec<Position> {
         let mut pos = Position::default();
+        let mut vertices = vec![pos];
         for inst in self.instructions.iter() {
-            perimeter += inst.steps;
             pos += inst.direction.into_step(inst.steps as isize);
-            dots.push(pos);
+            vertices.push(pos);
         }
 
-        let area = dots
-            .windows(2)
-            .map(|p| ((p[1].y + p[0].y) * (p[0].x - p[1].x)))
-            .sum::<isize>();
+        // a closed trench ends where it started
+        vertices.pop();
+        vertices
+    }
 
-        (area as usize) / 2 + perimeter / 2 + 1
+    /// The number of cubes dug for the trench itself
+    pub fn perimeter(&self) -> usize {
+        self.instructions.iter().map(|inst| inst.steps).sum()
+    }
+
+    /// Makes sure the trench is a simple closed loop
+    pub fn validate(&self) -> Result<(), DiggerErr> {
+        let edges = self.edges();
+        let end = edges.last().map(|(_, end)| *end).unwrap_or_default();
+        if end != Position::default() {
+            return Err(DiggerErr::OpenPath(end.x, end.y));
+        }
+
+        let len = self.instructions.len();
+        for a in 0..len {
+            for b in (a + 1)..len {
+                let adjacent = b == a + 1 || (a == 0 && b == len - 1);
+                let crossing = match adjacent {
+                    // consecutive edges always share a corner, they only
+                    // cross if the second goes back over the first one
+                    true => {
+                        let (first, second) = if b == a + 1 { (a, b) } else { (b, a) };
+                        self.instructions[first].direction.opposite()
+                            == self.instructions[second].direction
+                    }
+                    false => segments_touch(edges[a], edges[b]),
+                };
+
+                if crossing {
+                    return Err(DiggerErr::SelfIntersection(a, b));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The number of cubes dug inside the trench, by Pick's theorem
+    pub fn interior_points(&self) -> Result<usize, DiggerErr> {
+        self.validate()?;
+        if self.instructions.is_empty() {
+            return Ok(0);
+        }
+
+        let vertices = self.vertices();
+        let double_area = vertices
+            .iter()
+            .zip(vertices.iter().cycle().skip(1))
+            .map(|(a, b)| (b.y + a.y) * (a.x - b.x))
+            .sum::<isize>()
+            .unsigned_abs();
+
+        // the trench can enclose no cubes at all, so `+ 2` goes first to avoid underflowing
+        Ok((double_area + 2 - self.perimeter()) / 2)
+    }
+
+    pub fn calculate_area(&self) -> Result<usize, DiggerErr> {
+        Ok(self.interior_points()? + self.perimeter())
+    }
+
+    fn edges(&self) -> Vec<(Position, Position)> {
+        let mut pos = Position::default();
+        self.instructions
+            .iter()
+            .map(|inst| {
+                let start = pos;
+                pos += inst.direction.into_step(inst.steps as isize);
+                (start, pos)
+            })
+            .collect()
+    }
+
+    /// Draws the lagoon as an SVG scaled to fit a `size` x `size` viewport,
+    /// every edge of the trench is stroked in its own color
+    pub fn to_svg(&self, size: u32) -> String {
+        const MARGIN: f64 = 4.0;
+
+        let vertices = self.vertices();
+        let min_x = vertices.iter().map(|pos| pos.x).min().unwrap_or(0);
+        let max_x = vertices.iter().map(|pos| pos.x).max().unwrap_or(0);
+        let min_y = vertices.iter().map(|pos| pos.y).min().unwrap_or(0);
+        let max_y = vertices.iter().map(|pos| pos.y).max().unwrap_or(0);
+
+        // keep the aspect ratio, the part 2 plans span millions of cubes
+        let span = (max_x - min_x).max(max_y - min_y).max(1) as f64;
+        let scale = (size as f64 - 2.0 * MARGIN) / span;
+        let project = |pos: Position| {
+            (
+                MARGIN + (pos.x - min_x) as f64 * scale,
+                MARGIN + (pos.y - min_y) as f64 * scale,
+            )
+        };
+
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{0}\" viewBox=\"0 0 {0} {0}\">\n",
+            size
+        );
+
+        let points = vertices
+            .iter()
+            .map(|&pos| {
+                let (x, y) = project(pos);
+                format!("{:.2},{:.2}", x, y)
+            })
+            .collect::<Vec<_>>();
+        svg += &format!(
+            "  <polygon points=\"{}\" fill=\"#dddddd\" stroke=\"none\"/>\n",
+            points.join(" ")
+        );
+
+        for (inst, (start, end)) in self.instructions.iter().zip(self.edges()) {
+            let (x1, y1) = project(start);
+            let (x2, y2) = project(end);
+            svg += &format!(
+                "  <line x1=\"{:.2}\" y1=\"{:.2}\" x2=\"{:.2}\" y2=\"{:.2}\" stroke=\"{}\" stroke-width=\"2\" stroke-linecap=\"square\"/>\n",
+                x1, y1, x2, y2, inst.color
+            );
+        }
+
+        svg += "</svg>\n";
+        svg
+    }
+
+    /// Draws the lagoon, the trench is painted by the color of each edge
+    pub fn render(&self, colored: bool) -> Result<String, DiggerErr> {
+        self.validate()?;
+
+        let mut trench = HashMap::new();
+        for (inst, (start, _)) in self.instructions.iter().zip(self.edges()) {
+            for step in 0..=inst.steps {
+                // corners keep the color of the edge that dug them first
+                trench
+                    .entry(start + inst.direction.into_step(step as isize))
+                    .or_insert(inst.color);
+            }
+        }
+
+        let vertices = self.vertices();
+        let min_x = vertices.iter().map(|pos| pos.x).min().unwrap_or(0);
+        let max_x = vertices.iter().map(|pos| pos.x).max().unwrap_or(0);
+        let min_y = vertices.iter().map(|pos| pos.y).min().unwrap_or(0);
+        let max_y = vertices.iter().map(|pos| pos.y).max().unwrap_or(0);
+
+        let edges = self.edges();
+        let mut out = String::new();
+        for y in min_y..=max_y {
+            for x in min_x..=max_x {
+                let pos = Position::new(x, y);
+                match trench.get(&pos) {
+                    Some(color) if colored => out += &color.paint("#"),
+                    Some(_) => out.push('#'),
+                    None if is_inside(&edges, pos) => out.push('#'),
+                    None => out.push('.'),
+                }
+            }
+            out.push('\n');
+        }
+
+        Ok(out)
     }
 
     pub fn from_rgb(s: &str) -> Result<Self, DiggerErr> {
@@ -131,14 +310,57 @@ impl FromStr for Instruction {
         let steps = parts[1]
             .parse::<usize>()
             .map_err(|_| DiggerErr::BadSteps(parts[1].into()))?;
+        let color = parse_color(parts[2])?.parse()?;
 
-        Ok(Self { steps, direction })
+        Ok(Self {
+            steps,
+            direction,
+            color,
+        })
     }
 }
 
+// both segments are axis aligned, so they touch iff their bounding boxes do
+fn segments_touch(a: (Position, Position), b: (Position, Position)) -> bool {
+    let span = |from: isize, to: isize| (from.min(to), from.max(to));
+    let (ax, bx) = (span(a.0.x, a.1.x), span(b.0.x, b.1.x));
+    let (ay, by) = (span(a.0.y, a.1.y), span(b.0.y, b.1.y));
+
+    ax.0 <= bx.1 && bx.0 <= ax.1 && ay.0 <= by.1 && by.0 <= ay.1
+}
+
+// casts a ray to the right of a point that isn't on the trench, and counts
+// the vertical edges it crosses (each edge includes its top end only)
+fn is_inside(edges: &[(Position, Position)], pos: Position) -> bool {
+    edges
+        .iter()
+        .filter(|(start, end)| start.x == end.x && start.x > pos.x)
+        .filter(|(start, end)| start.y.min(end.y) <= pos.y && pos.y < start.y.max(end.y))
+        .count()
+        % 2
+        == 1
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{Direction, Instruction};
+    use crate::color::Rgb;
+
+    use super::{DiggerErr, Direction, Instruction, Plan};
+
+    const INPUT: &str = r#"R 6 (#70c710)
+D 5 (#0dc571)
+L 2 (#5713f0)
+D 2 (#d2c081)
+R 2 (#59c680)
+D 2 (#411b91)
+L 5 (#8ceee2)
+U 2 (#caa173)
+L 1 (#1b58a2)
+U 2 (#caa171)
+R 2 (#7807d2)
+U 3 (#a77fa3)
+L 2 (#015232)
+U 2 (#7a21e3)"#;
 
     #[test]
     fn parse_instruction() {
@@ -146,6 +368,7 @@ mod tests {
         let expected_output = Instruction {
             steps: 6,
             direction: Direction::Right,
+            color: Rgb::new(0x70, 0xc7, 0x10),
         };
 
         let output: Instruction = input.parse().unwrap();
@@ -158,9 +381,119 @@ mod tests {
         let expected_output = Instruction {
             steps: 461937,
             direction: Direction::Right,
+            color: Rgb::new(0x70, 0xc7, 0x10),
         };
 
         let output = Instruction::from_rgb(input).unwrap();
         assert_eq!(output, expected_output);
     }
+
+    #[test]
+    fn plan_geometry() {
+        let plan: Plan = INPUT.parse().unwrap();
+
+        assert_eq!(plan.vertices().len(), 14);
+        assert_eq!(plan.perimeter(), 38);
+        assert_eq!(plan.interior_points().unwrap(), 24);
+        assert_eq!(plan.calculate_area().unwrap(), 62);
+    }
+
+    #[test]
+    fn plan_without_interior() {
+        let square: Plan = "R 1 (#000000)\nD 1 (#000000)\nL 1 (#000000)\nU 1 (#000000)"
+            .parse()
+            .unwrap();
+        assert_eq!(square.interior_points().unwrap(), 0);
+        assert_eq!(square.calculate_area().unwrap(), 4);
+
+        let strip: Plan = "R 5 (#000000)\nD 1 (#000000)\nL 5 (#000000)\nU 1 (#000000)"
+            .parse()
+            .unwrap();
+        assert_eq!(strip.interior_points().unwrap(), 0);
+        assert_eq!(strip.calculate_area().unwrap(), 12);
+
+        let empty: Plan = "".parse().unwrap();
+        assert_eq!(empty.calculate_area().unwrap(), 0);
+    }
+
+    #[test]
+    fn validate_plan() {
+        let open: Plan = "R 2 (#000000)\nD 2 (#000000)".parse().unwrap();
+        assert!(matches!(open.validate(), Err(DiggerErr::OpenPath(2, 2))));
+
+        let backtrack: Plan = "R 2 (#000000)\nL 1 (#000000)\nL 1 (#000000)"
+            .parse()
+            .unwrap();
+        assert!(matches!(
+            backtrack.validate(),
+            Err(DiggerErr::SelfIntersection(0, 1))
+        ));
+
+        // a figure eight, the fourth edge crosses the first one
+        let crossing: Plan = r#"R 2 (#000000)
+D 2 (#000000)
+L 1 (#000000)
+U 3 (#000000)
+L 1 (#000000)
+D 1 (#000000)"#
+            .parse()
+            .unwrap();
+        assert!(matches!(
+            crossing.validate(),
+            Err(DiggerErr::SelfIntersection(0, 3))
+        ));
+    }
+
+    #[test]
+    fn export_svg() {
+        let plan: Plan = "R 2 (#ff0000)\nD 1 (#00ff00)\nL 2 (#0000ff)\nU 1 (#70c710)"
+            .parse()
+            .unwrap();
+
+        assert_eq!(
+            plan.to_svg(28),
+            r##"<svg xmlns="http://www.w3.org/2000/svg" width="28" height="28" viewBox="0 0 28 28">
+  <polygon points="4.00,4.00 24.00,4.00 24.00,14.00 4.00,14.00" fill="#dddddd" stroke="none"/>
+  <line x1="4.00" y1="4.00" x2="24.00" y2="4.00" stroke="#ff0000" stroke-width="2" stroke-linecap="square"/>
+  <line x1="24.00" y1="4.00" x2="24.00" y2="14.00" stroke="#00ff00" stroke-width="2" stroke-linecap="square"/>
+  <line x1="24.00" y1="14.00" x2="4.00" y2="14.00" stroke="#0000ff" stroke-width="2" stroke-linecap="square"/>
+  <line x1="4.00" y1="14.00" x2="4.00" y2="4.00" stroke="#70c710" stroke-width="2" stroke-linecap="square"/>
+</svg>
+"##
+        );
+
+        // the huge part 2 coordinates are scaled down into the viewport
+        let plan = Plan::from_rgb(INPUT).unwrap();
+        let svg = plan.to_svg(100);
+        assert!(svg.contains(r##"stroke="#70c710""##));
+        assert!(svg.contains("96.00"));
+    }
+
+    #[test]
+    fn render_plan() {
+        let plan: Plan = INPUT.parse().unwrap();
+
+        assert_eq!(
+            plan.render(false).unwrap(),
+            r#"#######
+#######
+#######
+..#####
+..#####
+#######
+#####..
+#######
+.######
+.######
+"#
+        );
+
+        let plan: Plan = "R 1 (#ff0000)\nD 1 (#00ff00)\nL 1 (#0000ff)\nU 1 (#ffffff)"
+            .parse()
+            .unwrap();
+        assert!(plan
+            .render(true)
+            .unwrap()
+            .starts_with("\x1b[38;2;255;0;0m#\x1b[0m\x1b[38;2;255;0;0m#\x1b[0m\n"));
+    }
 }
@@ -1,21 +1,30 @@
 use digger::{DiggerErr, Plan};
 
+mod color;
 mod digger;
 mod position;
 
 fn part_1(input: &str) -> Result<usize, DiggerErr> {
     let plan: Plan = input.parse()?;
-
-    Ok(plan.calculate_area())
+    tracing::debug!(
+        "lagoon:\n{}",
+        plan.render(false)
+            .unwrap_or_else(|err| format!("can't render the lagoon: {}", err))
+    );
+    tracing::trace!("lagoon as SVG:\n{}", plan.to_svg(512));
+
+    plan.calculate_area()
 }
 
 fn part_2(input: &str) -> Result<usize, DiggerErr> {
     let plan = Plan::from_rgb(input)?;
+    tracing::trace!("lagoon from the colors as SVG:\n{}", plan.to_svg(512));
 
-    Ok(plan.calculate_area())
+    plan.calculate_area()
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
+    tracing_subscriber::fmt::init();
     let input = rust_shared_utils::read_puzzle()?;
     println!("part 1: {}", part_1(&input)?);
     println!("part 2: {}", part_2(&input)?);
@@ -46,6 +46,15 @@ impl Direction {
         }
     }
 
+    pub const fn opposite(self) -> Self {
+        match self {
+            Self::Up => Self::Down,
+            Self::Down => Self::Up,
+            Self::Left => Self::Right,
+            Self::Right => Self::Left,
+        }
+    }
+
     pub fn from_digit(value: char) -> Option<Self> {
         let dir = match value.to_digit(10)? {
             0 => Self::Right,
@@ -0,0 +1,45 @@
+use std::fmt::{self, Display};
+
+/// A problem found in the workflows before processing any part
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Lint {
+    /// there is no `in` workflow to start from
+    MissingEntry,
+
+    /// the workflows on the path send parts back to the first one
+    Cycle(Vec<String>),
+
+    /// the workflow can't be reached from `in`
+    Unreachable(String),
+
+    /// the condition accepts every part that reaches it,
+    /// so the conditions after it are never checked
+    AlwaysTrue { workflow: String, condition: String },
+
+    /// the condition can't be met by any part that reaches it
+    NeverTrue { workflow: String, condition: String },
+
+    /// the target doesn't name any workflow
+    MissingTarget { workflow: String, target: String },
+}
+
+impl Display for Lint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingEntry => write!(f, "missing the in workflow"),
+            Self::Cycle(path) => write!(f, "cycle: {}", path.join(" -> ")),
+            Self::Unreachable(name) => write!(f, "{} is unreachable from in", name),
+            Self::AlwaysTrue {
+                workflow,
+                condition,
+            } => write!(f, "{}: {} is always true", workflow, condition),
+            Self::NeverTrue {
+                workflow,
+                condition,
+            } => write!(f, "{}: {} is never true", workflow, condition),
+            Self::MissingTarget { workflow, target } => {
+                write!(f, "{}: missing workflow {}", workflow, target)
+            }
+        }
+    }
+}
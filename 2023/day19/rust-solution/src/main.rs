@@ -1,9 +1,11 @@
-use part::Part;
+use part::{Categories, Part};
 use workflow::{WorkflowErr, Workflows};
 
 use crate::workflow::Decision;
 
+mod lint;
 mod part;
+mod tree;
 mod workflow;
 
 #[derive(Debug, Clone)]
@@ -20,12 +22,20 @@ fn parse_input(input: &str) -> Result<Input, Box<dyn std::error::Error>> {
         .lines()
         .map(|line| line.trim())
         .filter(|line| !line.is_empty())
-        .map(|line| line.parse::<Part>())
-        .collect::<Result<Vec<_>, _>>()?;
+        .collect::<Vec<_>>();
+
+    // the categories are named by the parts themselves
+    let categories = match parts.first() {
+        Some(part) => Categories::from_part(part)?,
+        None => Categories::default(),
+    };
 
     Ok(Input {
-        parts,
-        workflows: workflows.parse()?,
+        parts: parts
+            .into_iter()
+            .map(|line| categories.parse_part(line))
+            .collect::<Result<Vec<_>, _>>()?,
+        workflows: Workflows::parse(workflows, categories)?,
     })
 }
 
@@ -50,6 +60,9 @@ fn part_2(input: &Input) -> Result<usize, WorkflowErr> {
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let input = rust_shared_utils::read_puzzle()?;
     let input = parse_input(&input)?;
+    for lint in input.workflows.lint() {
+        println!("lint: {}", lint);
+    }
     println!("part 1: {}", part_1(&input)?);
     println!("part 2: {}", part_2(&input)?);
 
@@ -87,4 +100,18 @@ hdj{m>838:A,pv}
         let input = super::parse_input(INPUT).unwrap();
         assert_eq!(super::part_2(&input).unwrap(), 167409079868000);
     }
+
+    #[test]
+    fn variant_rules() {
+        let input = r#"in{cool>=5:check,R}
+check{shiny==3:R,A}
+
+{cool=5,shiny=3}
+{cool=9,shiny=1}
+{cool=1,shiny=1}"#;
+
+        let input = super::parse_input(input).unwrap();
+        assert_eq!(super::part_1(&input).unwrap(), 10);
+        assert_eq!(super::part_2(&input).unwrap(), 3996 * 3999);
+    }
 }
@@ -1,103 +1,237 @@
-use std::{ops::Range, str::FromStr};
+use std::{
+    fmt::{self, Display},
+    ops::Range,
+    str::FromStr,
+};
 
 use once_cell::sync::Lazy;
 use regex::Regex;
 
+const MIN_RATING: u32 = 1;
+const MAX_RATING: u32 = 4000;
+
 #[derive(thiserror::Error, Debug)]
 pub enum PartErr {
     #[error("unknown part format")]
     UnknownFormat,
 
     #[error("bad '{0}' rating")]
-    BadRating(char),
+    BadRating(String),
+
+    #[error("unknown category '{0}'")]
+    UnknownCategory(String),
+
+    #[error("missing '{0}' rating")]
+    MissingRating(String),
+}
+
+/// The names of the ratings every part has, in the order they are stored
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Categories {
+    names: Vec<String>,
+}
+
+impl Categories {
+    pub fn new(names: Vec<String>) -> Self {
+        Self { names }
+    }
+
+    /// Takes the category names in the order they appear in the part
+    pub fn from_part(s: &str) -> Result<Self, PartErr> {
+        let names = split_ratings(s)?
+            .into_iter()
+            .map(|(name, _)| name.to_string())
+            .collect();
+
+        Ok(Self { names })
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn index_of(&self, name: &str) -> Option<usize> {
+        self.names.iter().position(|category| category == name)
+    }
+
+    pub fn name(&self, idx: usize) -> &str {
+        &self.names[idx]
+    }
+
+    pub fn parse_part(&self, s: &str) -> Result<Part, PartErr> {
+        let mut ratings = vec![None; self.len()];
+        for (name, rating) in split_ratings(s)? {
+            let idx = self
+                .index_of(name)
+                .ok_or_else(|| PartErr::UnknownCategory(name.into()))?;
+
+            ratings[idx] = Some(
+                rating
+                    .parse()
+                    .map_err(|_| PartErr::BadRating(name.into()))?,
+            );
+        }
+
+        let ratings = ratings
+            .into_iter()
+            .enumerate()
+            .map(|(idx, rating)| {
+                rating.ok_or_else(|| PartErr::MissingRating(self.name(idx).into()))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Part { ratings })
+    }
+}
+
+impl Default for Categories {
+    fn default() -> Self {
+        Self::new(["x", "m", "a", "s"].map(String::from).to_vec())
+    }
+}
+
+fn split_ratings(s: &str) -> Result<Vec<(&str, &str)>, PartErr> {
+    static RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\{(\w+=\d+(?:,\w+=\d+)*)\}$").unwrap());
+
+    let ratings = RE.captures(s.trim()).ok_or(PartErr::UnknownFormat)?;
+    Ok(ratings
+        .get(1)
+        .unwrap()
+        .as_str()
+        .split(',')
+        .map(|rating| rating.split_once('=').unwrap())
+        .collect())
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Part {
-    pub x: u32,
-    pub m: u32,
-    pub a: u32,
-    pub s: u32,
+    pub ratings: Vec<u32>,
 }
 
 impl Part {
-    pub fn sum_all(self) -> u32 {
-        self.x + self.m + self.a + self.s
+    pub fn sum_all(&self) -> u32 {
+        self.ratings.iter().sum()
     }
 }
 
+/// Parses the part using its own categories order
 impl FromStr for Part {
     type Err = PartErr;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        static RE: Lazy<Regex> =
-            Lazy::new(|| Regex::new(r"^\{x=(\d+),m=(\d+),a=(\d+),s=(\d+)\}$").unwrap());
-
-        let ratings = RE.captures(s.trim()).ok_or(PartErr::UnknownFormat)?;
-        macro_rules! get_rating {
-            ($num:literal, $tag:literal) => {{
-                ratings
-                    .get($num)
-                    .unwrap()
-                    .as_str()
-                    .parse()
-                    .map_err(|_| PartErr::BadRating($tag))?
-            }};
-        }
-
-        Ok(Self {
-            x: get_rating!(1, 'x'),
-            m: get_rating!(2, 'm'),
-            a: get_rating!(3, 'a'),
-            s: get_rating!(4, 's'),
-        })
+        Categories::from_part(s)?.parse_part(s)
     }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct PartRange {
-    pub x: Range<u32>,
-    pub m: Range<u32>,
-    pub a: Range<u32>,
-    pub s: Range<u32>,
+    pub ranges: Vec<Range<u32>>,
 }
 
 impl PartRange {
-    pub fn new() -> Self {
+    /// Every possible part with `dimensions` categories
+    pub fn new(dimensions: usize) -> Self {
         Self {
-            x: (1..4001),
-            m: (1..4001),
-            a: (1..4001),
-            s: (1..4001),
+            ranges: vec![MIN_RATING..(MAX_RATING + 1); dimensions],
         }
     }
 
     pub fn size(&self) -> usize {
-        self.x.len() * self.m.len() * self.a.len() * self.s.len()
+        self.ranges.iter().map(|range| range.len()).product()
+    }
+
+    /// Splits the range into the parts whose `category` rating
+    /// is inside `interval`, and the (up to two) parts that aren't.
+    pub fn split(
+        &self,
+        category: usize,
+        interval: Range<u32>,
+    ) -> (Option<PartRange>, Vec<PartRange>) {
+        let range = &self.ranges[category];
+        let with = |new: Range<u32>| {
+            (!new.is_empty()).then(|| {
+                let mut prange = self.clone();
+                prange.ranges[category] = new;
+                prange
+            })
+        };
+
+        let inside = with(range.start.max(interval.start)..range.end.min(interval.end));
+        let outside = [
+            with(range.start..range.end.min(interval.start)),
+            with(range.start.max(interval.end)..range.end),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+
+        (inside, outside)
     }
 }
 
-impl Default for PartRange {
-    fn default() -> Self {
-        Self::new()
+impl Display for PartRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ranges = self
+            .ranges
+            .iter()
+            .map(|range| format!("{:?}", range))
+            .collect::<Vec<_>>();
+
+        write!(f, "{{{}}}", ranges.join(","))
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::Part;
+    use super::{Categories, Part, PartRange};
 
     #[test]
     fn part_part() {
         let input = r#"{x=787,m=2655,a=1222,s=2876}"#;
         let expected_output = Part {
-            x: 787,
-            m: 2655,
-            a: 1222,
-            s: 2876,
+            ratings: vec![787, 2655, 1222, 2876],
         };
 
         let output: Part = input.parse().unwrap();
         assert_eq!(output, expected_output);
     }
+
+    #[test]
+    fn parse_part_with_categories() {
+        let categories = Categories::from_part("{cool=1,shiny=2}").unwrap();
+        assert_eq!(categories.index_of("shiny"), Some(1));
+
+        let part = categories.parse_part("{shiny=5,cool=7}").unwrap();
+        assert_eq!(part.ratings, vec![7, 5]);
+
+        assert!(categories.parse_part("{cool=1}").is_err());
+        assert!(categories.parse_part("{cool=1,shiny=2,x=3}").is_err());
+    }
+
+    #[test]
+    fn split_part_range() {
+        let range = PartRange::new(2);
+
+        let (inside, outside) = range.split(1, 100..101);
+        assert_eq!(inside.unwrap().ranges, vec![1..4001, 100..101]);
+        assert_eq!(
+            outside
+                .into_iter()
+                .map(|prange| prange.ranges)
+                .collect::<Vec<_>>(),
+            vec![vec![1..4001, 1..100], vec![1..4001, 101..4001]]
+        );
+
+        let (inside, outside) = range.split(0, 0..1);
+        assert!(inside.is_none());
+        assert_eq!(outside, vec![range]);
+    }
+
+    #[test]
+    fn display_part_range() {
+        let mut range = PartRange::new(4);
+        range.ranges[1] = 839..1801;
+
+        assert_eq!(range.to_string(), "{1..4001,839..1801,1..4001,1..4001}");
+    }
 }
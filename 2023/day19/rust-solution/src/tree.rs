@@ -0,0 +1,191 @@
+use std::{
+    fmt::{self, Display},
+    rc::Rc,
+};
+
+use crate::{
+    part::{Part, PartRange},
+    workflow::{Category, Decision, Sign, WorkflowErr},
+};
+
+/// The workflows flattened into a single decision tree, starting at `in`,
+/// workflows reached from several places share the same subtree
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecisionTree {
+    Leaf(Decision),
+    /// a target that doesn't name any workflow, reaching it is an error
+    Missing(String),
+    Branch {
+        category: Category,
+        sign: Sign,
+        right: u32,
+        inside: Rc<DecisionTree>,
+        outside: Rc<DecisionTree>,
+    },
+}
+
+impl DecisionTree {
+    pub fn branch(
+        category: Category,
+        sign: Sign,
+        right: u32,
+        inside: Rc<DecisionTree>,
+        outside: Rc<DecisionTree>,
+    ) -> Rc<Self> {
+        // both paths lead to the same place, the condition doesn't matter,
+        // shared subtrees are only compared by address to not walk every path
+        let same = match (inside.as_ref(), outside.as_ref()) {
+            (Self::Branch { .. }, _) | (_, Self::Branch { .. }) => Rc::ptr_eq(&inside, &outside),
+            _ => inside == outside,
+        };
+        if same {
+            return inside;
+        }
+
+        Rc::new(Self::Branch {
+            category,
+            sign,
+            right,
+            inside,
+            outside,
+        })
+    }
+
+    pub fn decide(&self, part: &Part) -> Result<Decision, WorkflowErr> {
+        let mut current = self;
+        loop {
+            match current {
+                Self::Leaf(decision) => return Ok(*decision),
+                Self::Missing(name) => return Err(WorkflowErr::MissingWorkflow(name.clone())),
+                Self::Branch {
+                    category,
+                    sign,
+                    right,
+                    inside,
+                    outside,
+                } => {
+                    current = match sign.check(category.extract_part(part), *right) {
+                        true => inside,
+                        false => outside,
+                    }
+                }
+            }
+        }
+    }
+
+    /// Every accepted part with `dimensions` categories
+    /// lies in exactly one of the returned ranges
+    pub fn accepted_ranges(&self, dimensions: usize) -> Result<Vec<PartRange>, WorkflowErr> {
+        let mut accepted = vec![];
+
+        let mut frontier = vec![(self, PartRange::new(dimensions))];
+        while let Some((node, range)) = frontier.pop() {
+            match node {
+                Self::Leaf(Decision::Accept) => accepted.push(range),
+                Self::Leaf(Decision::Reject) => {}
+                Self::Missing(name) => return Err(WorkflowErr::MissingWorkflow(name.clone())),
+                Self::Branch {
+                    category,
+                    sign,
+                    right,
+                    inside,
+                    outside,
+                } => {
+                    let (irange, orange) = category.split_range(*sign, *right, &range);
+                    if let Some(irange) = irange {
+                        frontier.push((inside, irange));
+                    }
+                    frontier.extend(orange.into_iter().map(|orange| (outside.as_ref(), orange)));
+                }
+            }
+        }
+
+        Ok(accepted)
+    }
+
+    fn write_rust(&self, f: &mut fmt::Formatter<'_>, depth: usize) -> fmt::Result {
+        let indent = "    ".repeat(depth);
+        match self {
+            Self::Leaf(decision) => writeln!(f, "{}{}", indent, *decision == Decision::Accept),
+            Self::Missing(name) => writeln!(f, "{}panic!(\"missing workflow: {}\")", indent, name),
+            Self::Branch {
+                category,
+                sign,
+                right,
+                inside,
+                outside,
+            } => {
+                writeln!(f, "{}if {} {} {} {{", indent, category, sign, right)?;
+                inside.write_rust(f, depth + 1)?;
+                writeln!(f, "{}}} else {{", indent)?;
+                outside.write_rust(f, depth + 1)?;
+                writeln!(f, "{}}}", indent)
+            }
+        }
+    }
+}
+
+/// Renders the tree as a rust expression that evaluates
+/// to `true` when the part is accepted
+impl Display for DecisionTree {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_rust(f, 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use crate::{
+        part::PartRange,
+        workflow::{Category, Decision, Sign},
+    };
+
+    use super::DecisionTree;
+
+    #[test]
+    fn collapse_identical_branches() {
+        let reject = Rc::new(DecisionTree::Leaf(Decision::Reject));
+        let tree = DecisionTree::branch(
+            Category::new(2, "a".into()),
+            Sign::More,
+            3333,
+            reject.clone(),
+            Rc::new(DecisionTree::Leaf(Decision::Reject)),
+        );
+
+        assert_eq!(*tree, DecisionTree::Leaf(Decision::Reject));
+    }
+
+    #[test]
+    fn accepted_ranges() {
+        let tree = DecisionTree::branch(
+            Category::new(0, "x".into()),
+            Sign::Less,
+            1001,
+            Rc::new(DecisionTree::Leaf(Decision::Accept)),
+            Rc::new(DecisionTree::Leaf(Decision::Reject)),
+        );
+
+        let mut expected = PartRange::new(4);
+        expected.ranges[0] = 1..1001;
+        assert_eq!(tree.accepted_ranges(4).unwrap(), vec![expected]);
+    }
+
+    #[test]
+    fn render_rust() {
+        let tree = DecisionTree::branch(
+            Category::new(3, "s".into()),
+            Sign::More,
+            2770,
+            Rc::new(DecisionTree::Leaf(Decision::Accept)),
+            Rc::new(DecisionTree::Leaf(Decision::Reject)),
+        );
+
+        assert_eq!(
+            tree.to_string(),
+            "if s > 2770 {\n    true\n} else {\n    false\n}\n"
+        );
+    }
+}
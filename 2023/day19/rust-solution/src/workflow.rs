@@ -1,10 +1,19 @@
-use std::{collections::HashMap, ops::Range, str::FromStr};
-
-use once_cell::sync::Lazy;
-use paste::paste;
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::{self, Display},
+    ops::Range,
+    rc::Rc,
+    str::FromStr,
+};
+
+use once_cell::sync::{Lazy, OnceCell};
 use regex::Regex;
 
-use crate::part::{Part, PartRange};
+use crate::{
+    lint::Lint,
+    part::{Categories, Part, PartRange},
+    tree::DecisionTree,
+};
 
 #[derive(thiserror::Error, Debug)]
 pub enum WorkflowErr {
@@ -16,57 +25,99 @@ pub enum WorkflowErr {
 
     #[error("missing workflow: {0}")]
     MissingWorkflow(String),
+
+    #[error("unknown category: {0}")]
+    UnknownCategory(String),
+
+    #[error("workflows form a cycle: {0}")]
+    Cycle(String),
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum Sign {
+pub enum Sign {
     Less,
+    LessEq,
     More,
+    MoreEq,
+    Equal,
 }
 
 impl Sign {
-    fn check(self, left: u32, right: u32) -> bool {
+    pub fn check(self, left: u32, right: u32) -> bool {
         match self {
             Self::Less => left < right,
+            Self::LessEq => left <= right,
             Self::More => left > right,
+            Self::MoreEq => left >= right,
+            Self::Equal => left == right,
+        }
+    }
+
+    /// The values of `left` that pass the comparison
+    pub fn interval(self, right: u32) -> Range<u32> {
+        match self {
+            Self::Less => 0..right,
+            Self::LessEq => 0..right.saturating_add(1),
+            Self::More => right.saturating_add(1)..u32::MAX,
+            Self::MoreEq => right..u32::MAX,
+            Self::Equal => right..right.saturating_add(1),
         }
     }
 
-    fn from_char(ch: char) -> Option<Self> {
-        match ch {
-            '>' => Some(Self::More),
-            '<' => Some(Self::Less),
+    fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "<" => Some(Self::Less),
+            "<=" => Some(Self::LessEq),
+            ">" => Some(Self::More),
+            ">=" => Some(Self::MoreEq),
+            "==" => Some(Self::Equal),
             _ => None,
         }
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum Category {
-    X,
-    M,
-    A,
-    S,
+impl Display for Sign {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Less => write!(f, "<"),
+            Self::LessEq => write!(f, "<="),
+            Self::More => write!(f, ">"),
+            Self::MoreEq => write!(f, ">="),
+            Self::Equal => write!(f, "=="),
+        }
+    }
+}
+
+/// A named rating, and where to find it in a part
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Category {
+    idx: usize,
+    name: String,
 }
 
 impl Category {
-    fn extract_part(self, part: &Part) -> u32 {
-        match self {
-            Self::A => part.a,
-            Self::S => part.s,
-            Self::M => part.m,
-            Self::X => part.x,
-        }
+    pub fn new(idx: usize, name: String) -> Self {
+        Self { idx, name }
     }
 
-    fn from_char(ch: char) -> Option<Self> {
-        Some(match ch {
-            'x' => Self::X,
-            'm' => Self::M,
-            'a' => Self::A,
-            's' => Self::S,
-            _ => return None,
-        })
+    pub fn extract_part(&self, part: &Part) -> u32 {
+        part.ratings[self.idx]
+    }
+
+    /// Splits the range into the parts that pass and fail the comparison
+    pub fn split_range(
+        &self,
+        sign: Sign,
+        right: u32,
+        range: &PartRange,
+    ) -> (Option<PartRange>, Vec<PartRange>) {
+        range.split(self.idx, sign.interval(right))
+    }
+}
+
+impl Display for Category {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)
     }
 }
 
@@ -88,6 +139,16 @@ impl Target {
     }
 }
 
+impl Display for Target {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Reject => write!(f, "R"),
+            Self::Accept => write!(f, "A"),
+            Self::Map(name) => write!(f, "{}", name),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 struct Condition {
     cateogry: Category,
@@ -97,100 +158,30 @@ struct Condition {
 }
 
 impl Condition {
-    fn check(&self, part: &Part) -> bool {
-        let left = self.cateogry.extract_part(part);
-        self.sign.check(left, self.right)
-    }
-
-    fn split_range(&self, range: &PartRange) -> (Option<PartRange>, Option<PartRange>) {
-        let break_range = |range: Range<u32>| match self.sign {
-            Sign::Less => {
-                let inside = if range.start < self.right {
-                    Some(range.start..self.right.min(range.end))
-                } else {
-                    None
-                };
-
-                let outside = if range.end > self.right {
-                    Some(self.right..range.end)
-                } else {
-                    None
-                };
-
-                (inside, outside)
-            }
-            Sign::More => {
-                let inside = if range.end > (self.right + 1) {
-                    Some(range.start.max(self.right + 1)..range.end)
-                } else {
-                    None
-                };
-
-                let outside = if range.start <= self.right {
-                    Some(range.start..(self.right + 1))
-                } else {
-                    None
-                };
-
-                (inside, outside)
-            }
-        };
-
-        macro_rules! edit_range {
-            ($category: tt) => {{
-                let (inside, outside) = break_range(range.$category.clone());
-                let inside = inside.map(|irange| {
-                    let mut prange = range.clone();
-                    prange.$category = irange;
-                    prange
-                });
-
-                let outside = outside.map(|irange| {
-                    let mut prange = range.clone();
-                    prange.$category = irange;
-                    prange
-                });
-
-                (inside, outside)
-            }};
-        }
+    fn split_range(&self, range: &PartRange) -> (Option<PartRange>, Vec<PartRange>) {
+        self.cateogry.split_range(self.sign, self.right, range)
+    }
 
-        match self.cateogry {
-            Category::A => edit_range!(a),
-            Category::M => edit_range!(m),
-            Category::S => edit_range!(s),
-            Category::X => edit_range!(x),
-        }
+    /// Splits every range into the parts that meet the condition, and the parts that don't
+    fn split_ranges(&self, ranges: &[PartRange]) -> (Vec<PartRange>, Vec<PartRange>) {
+        ranges.iter().map(|range| self.split_range(range)).fold(
+            (vec![], vec![]),
+            |(mut irange, mut orange), (inside, outside)| {
+                irange.extend(inside);
+                orange.extend(outside);
+                (irange, orange)
+            },
+        )
     }
 }
 
-impl From<Condition> for PartRange {
-    fn from(value: Condition) -> Self {
-        let mut range = PartRange::new();
-
-        macro_rules! edit_range {
-            ($category: tt) => {
-                paste! {
-                    match value.sign {
-                        Sign::Less => {
-                            range.$category.end = value.right;
-                        }
-                        Sign::More => {
-                            range.$category.start = (value.right + 1);
-                        }
-                    }
-                }
-            };
-        }
-
-        match value.cateogry {
-            Category::A => edit_range!(a),
-            Category::M => edit_range!(m),
-            Category::S => edit_range!(s),
-            Category::X => edit_range!(x),
-        }
-
-        range
+impl Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}{}{}:{}",
+            self.cateogry, self.sign, self.right, self.target
+        )
     }
 }
 
@@ -202,20 +193,49 @@ struct Workflow {
 }
 
 impl Workflow {
+    /// Where the workflow sends the part
+    #[cfg(test)]
     fn apply(&self, part: &Part) -> &Target {
         self.conditions
             .iter()
-            .find(|condition| condition.check(part))
-            .map(|condition| &condition.target)
+            .find(|cond| {
+                cond.sign
+                    .check(cond.cateogry.extract_part(part), cond.right)
+            })
+            .map(|cond| &cond.target)
             .unwrap_or(&self.default)
     }
+
+    fn targets(&self) -> impl Iterator<Item = &Target> {
+        self.conditions
+            .iter()
+            .map(|cond| &cond.target)
+            .chain(std::iter::once(&self.default))
+    }
+
+    fn mapped(&self) -> impl Iterator<Item = &str> {
+        self.targets().filter_map(|target| match target {
+            Target::Map(name) => Some(name.as_str()),
+            _ => None,
+        })
+    }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone)]
 pub struct Workflows {
     data: HashMap<String, Workflow>,
+    categories: Categories,
+    tree: OnceCell<Rc<DecisionTree>>,
 }
 
+impl PartialEq for Workflows {
+    fn eq(&self, other: &Self) -> bool {
+        self.data == other.data && self.categories == other.categories
+    }
+}
+
+impl Eq for Workflows {}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Decision {
     Reject,
@@ -224,63 +244,272 @@ pub enum Decision {
 
 impl Workflows {
     pub fn process(&self, part: &Part) -> Result<Decision, WorkflowErr> {
-        let mut current = self.get_workflow("in")?;
-        loop {
-            let target = current.apply(part);
-            match target {
-                Target::Reject => return Ok(Decision::Reject),
-                Target::Accept => return Ok(Decision::Accept),
-                Target::Map(name) => current = self.get_workflow(name)?,
-            }
-        }
+        self.compile()?.decide(part)
     }
+
     fn get_workflow(&self, name: &str) -> Result<&Workflow, WorkflowErr> {
         self.data
             .get(name)
             .ok_or_else(|| WorkflowErr::MissingWorkflow(name.into()))
     }
 
+    /// Flattens the workflows reachable from `in` into a single decision tree,
+    /// the tree is compiled once and cached for the following calls.
+    pub fn compile(&self) -> Result<&DecisionTree, WorkflowErr> {
+        self.tree
+            .get_or_try_init(|| {
+                let mut compiled = HashMap::new();
+                self.compile_target(&Target::Map("in".into()), &mut vec![], &mut compiled)
+            })
+            .map(Rc::as_ref)
+    }
+
+    // every workflow is compiled once, and shared by all the rules that send parts to it
+    fn compile_target(
+        &self,
+        target: &Target,
+        path: &mut Vec<String>,
+        compiled: &mut HashMap<String, Rc<DecisionTree>>,
+    ) -> Result<Rc<DecisionTree>, WorkflowErr> {
+        let name = match target {
+            Target::Accept => return Ok(Rc::new(DecisionTree::Leaf(Decision::Accept))),
+            Target::Reject => return Ok(Rc::new(DecisionTree::Leaf(Decision::Reject))),
+            Target::Map(name) => name,
+        };
+
+        if let Some(tree) = compiled.get(name) {
+            return Ok(tree.clone());
+        }
+
+        if path.contains(name) {
+            path.push(name.clone());
+            return Err(WorkflowErr::Cycle(path.join(" -> ")));
+        }
+
+        let Ok(workflow) = self.get_workflow(name) else {
+            return Ok(Rc::new(DecisionTree::Missing(name.clone())));
+        };
+        path.push(name.clone());
+
+        let mut tree = self.compile_target(&workflow.default, path, compiled)?;
+        for cond in workflow.conditions.iter().rev() {
+            let inside = self.compile_target(&cond.target, path, compiled)?;
+            tree = DecisionTree::branch(cond.cateogry.clone(), cond.sign, cond.right, inside, tree);
+        }
+
+        path.pop();
+        compiled.insert(name.clone(), tree.clone());
+        Ok(tree)
+    }
+
+    /// The accepted region as a list of disjoint ranges
+    pub fn accepted_ranges(&self) -> Result<Vec<PartRange>, WorkflowErr> {
+        self.compile()?.accepted_ranges(self.categories.len())
+    }
+
     pub fn count_accepted_combinations(&self) -> Result<usize, WorkflowErr> {
-        let mut combinations = 0;
+        Ok(self.accepted_ranges()?.iter().map(PartRange::size).sum())
+    }
 
-        let mut frontier = vec![(self.get_workflow("in")?, PartRange::default())];
-        while let Some((workflow, start_range)) = frontier.pop() {
-            let mut outside = Some(start_range);
-            for cond in workflow.conditions.iter() {
-                let Some(range) = outside.as_ref() else { break };
-                let (irange, orange) = cond.split_range(range);
-                if let Some(irange) = irange {
-                    match &cond.target {
-                        Target::Accept => combinations += irange.size(),
-                        Target::Map(name) => frontier.push((self.get_workflow(name)?, irange)),
-                        Target::Reject => {}
-                    };
+    /// Reports every problem that `process` would only hit lazily (or never)
+    pub fn lint(&self) -> Vec<Lint> {
+        let mut names = self
+            .data
+            .keys()
+            .map(|name| name.as_str())
+            .collect::<Vec<_>>();
+        names.sort_unstable();
+
+        let mut lints = vec![];
+        let has_entry = self.data.contains_key("in");
+        if !has_entry {
+            lints.push(Lint::MissingEntry);
+        }
+
+        // the workflows that can't be reached from `in` are checked against every part
+        let incoming = self.incoming_ranges();
+        let every_part = vec![PartRange::new(self.categories.len())];
+
+        for name in names.iter() {
+            let workflow = &self.data[*name];
+            lints.extend(
+                workflow
+                    .mapped()
+                    .filter(|target| !self.data.contains_key(*target))
+                    .map(|target| Lint::MissingTarget {
+                        workflow: workflow.name.clone(),
+                        target: target.into(),
+                    }),
+            );
+
+            lints.extend(self.lint_conditions(workflow, incoming.get(name).unwrap_or(&every_part)));
+        }
+
+        let mut visited = HashSet::new();
+        for name in names.iter() {
+            self.find_cycles(name, &mut vec![], &mut visited, &mut lints);
+        }
+
+        // without an entry every workflow is unreachable, which says nothing new
+        if !has_entry {
+            return lints;
+        }
+
+        let mut reachable = HashSet::new();
+        let mut frontier = vec!["in"];
+        while let Some(name) = frontier.pop() {
+            let Some(workflow) = self.data.get(name) else {
+                continue;
+            };
+
+            if reachable.insert(name) {
+                frontier.extend(workflow.mapped());
+            }
+        }
+
+        lints.extend(
+            names
+                .iter()
+                .filter(|name| !reachable.contains(*name))
+                .map(|name| Lint::Unreachable(name.to_string())),
+        );
+
+        lints
+    }
+
+    // checks every condition against the ranges that reach the workflow,
+    // minus the ranges taken by the conditions before it
+    fn lint_conditions(&self, workflow: &Workflow, ranges: &[PartRange]) -> Vec<Lint> {
+        let mut lints = vec![];
+
+        // no part reaches the workflow, the dead conditions upstream already say so
+        if ranges.is_empty() {
+            return lints;
+        }
+
+        let mut outside = ranges.to_vec();
+        for cond in workflow.conditions.iter() {
+            let (irange, orange) = cond.split_ranges(&outside);
+
+            if irange.is_empty() {
+                lints.push(Lint::NeverTrue {
+                    workflow: workflow.name.clone(),
+                    condition: cond.to_string(),
+                });
+            } else if orange.is_empty() {
+                lints.push(Lint::AlwaysTrue {
+                    workflow: workflow.name.clone(),
+                    condition: cond.to_string(),
+                });
+            }
+
+            outside = orange;
+        }
+
+        lints
+    }
+
+    // the ranges of parts that reach every workflow from `in`, in topological order.
+    // the parts sent back along a cycle are dropped, the cycle is reported on its own
+    fn incoming_ranges(&self) -> HashMap<&str, Vec<PartRange>> {
+        let mut order = vec![];
+        self.postorder("in", &mut HashSet::new(), &mut order);
+        order.reverse();
+
+        let rank = order
+            .iter()
+            .enumerate()
+            .map(|(rank, name)| (*name, rank))
+            .collect::<HashMap<_, _>>();
+        let mut incoming = order
+            .iter()
+            .map(|name| (*name, vec![]))
+            .collect::<HashMap<_, _>>();
+        if let Some(ranges) = incoming.get_mut("in") {
+            ranges.push(PartRange::new(self.categories.len()));
+        }
+
+        for (idx, name) in order.iter().enumerate() {
+            let workflow = &self.data[*name];
+            let mut outside = incoming[name].clone();
+            let mut send = |target: &Target, ranges: Vec<PartRange>| {
+                if let Target::Map(target) = target {
+                    if rank.get(target.as_str()).is_some_and(|&rank| rank > idx) {
+                        incoming.get_mut(target.as_str()).unwrap().extend(ranges);
+                    }
                 }
+            };
 
+            for cond in workflow.conditions.iter() {
+                let (irange, orange) = cond.split_ranges(&outside);
+                send(&cond.target, irange);
                 outside = orange;
             }
+            send(&workflow.default, outside);
+        }
 
-            if let Some(outside) = outside {
-                match &workflow.default {
-                    Target::Accept => combinations += outside.size(),
-                    Target::Map(name) => frontier.push((self.get_workflow(name)?, outside)),
-                    Target::Reject => {}
-                };
-            }
+        incoming
+    }
+
+    fn postorder<'a>(
+        &'a self,
+        name: &'a str,
+        visited: &mut HashSet<&'a str>,
+        order: &mut Vec<&'a str>,
+    ) {
+        let Some(workflow) = self.data.get(name) else {
+            return;
+        };
+        if !visited.insert(name) {
+            return;
         }
 
-        Ok(combinations)
+        for target in workflow.mapped() {
+            self.postorder(target, visited, order);
+        }
+        order.push(name);
     }
-}
 
-impl FromStr for Workflow {
-    type Err = WorkflowErr;
+    fn find_cycles<'a>(
+        &'a self,
+        name: &'a str,
+        path: &mut Vec<&'a str>,
+        visited: &mut HashSet<&'a str>,
+        lints: &mut Vec<Lint>,
+    ) {
+        if let Some(idx) = path.iter().position(|step| *step == name) {
+            let mut cycle = path[idx..]
+                .iter()
+                .map(|step| step.to_string())
+                .collect::<Vec<_>>();
+            cycle.push(name.into());
+
+            lints.push(Lint::Cycle(cycle));
+            return;
+        }
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        static WORKFLOW_RE: Lazy<Regex> =
-            Lazy::new(|| Regex::new(r"^(\w+)\{((?:[xmas][><]\d+:\w+,)*)(\w+)\}$").unwrap());
+        let Some(workflow) = self.data.get(name) else {
+            return;
+        };
+        if !visited.insert(name) {
+            return;
+        }
+
+        path.push(name);
+        for target in workflow.mapped() {
+            self.find_cycles(target, path, visited, lints);
+        }
+        path.pop();
+    }
+}
+
+impl Workflow {
+    fn parse(s: &str, categories: &Categories) -> Result<Self, WorkflowErr> {
+        static WORKFLOW_RE: Lazy<Regex> = Lazy::new(|| {
+            Regex::new(r"^(\w+)\{((?:\w+(?:<=|>=|==|<|>)\d+:\w+,)*)(\w+)\}$").unwrap()
+        });
         static CONDITION_RE: Lazy<Regex> =
-            Lazy::new(|| Regex::new(r"([xmas])([><])(\d+):(\w+)").unwrap());
+            Lazy::new(|| Regex::new(r"(\w+?)(<=|>=|==|<|>)(\d+):(\w+)").unwrap());
 
         let parts = WORKFLOW_RE.captures(s).ok_or(WorkflowErr::UnknwonFormat)?;
         let name = parts.get(1).unwrap().as_str();
@@ -291,9 +520,13 @@ impl FromStr for Workflow {
             .captures_iter(conditions)
             .map(|cap| cap.extract())
             .map(|(_, [cateogry, sign, right, target])| {
+                let idx = categories
+                    .index_of(cateogry)
+                    .ok_or_else(|| WorkflowErr::UnknownCategory(cateogry.into()))?;
+
                 Ok(Condition {
-                    cateogry: Category::from_char(cateogry.trim().chars().next().unwrap()).unwrap(),
-                    sign: Sign::from_char(sign.trim().chars().next().unwrap()).unwrap(),
+                    cateogry: Category::new(idx, cateogry.into()),
+                    sign: Sign::from_str(sign).unwrap(),
                     right: right
                         .trim()
                         .parse()
@@ -311,31 +544,52 @@ impl FromStr for Workflow {
     }
 }
 
-impl FromStr for Workflows {
+impl FromStr for Workflow {
     type Err = WorkflowErr;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s, &Categories::default())
+    }
+}
+
+impl Workflows {
+    /// Parses workflows that rate parts by the given categories
+    pub fn parse(s: &str, categories: Categories) -> Result<Self, WorkflowErr> {
         let data = s
             .trim()
             .lines()
             .map(|line| {
-                line.parse::<Workflow>()
+                Workflow::parse(line.trim(), &categories)
                     .map(|workflow| (workflow.name.clone(), workflow))
             })
             .collect::<Result<HashMap<_, _>, _>>()?;
 
-        Ok(Self { data })
+        Ok(Self {
+            data,
+            categories,
+            tree: OnceCell::new(),
+        })
+    }
+}
+
+/// Parses workflows for the standard `xmas` parts
+impl FromStr for Workflows {
+    type Err = WorkflowErr;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s, Categories::default())
     }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::{
-        part::Part,
-        workflow::{Decision, Target, Workflows},
+        lint::Lint,
+        part::{Categories, Part},
+        workflow::{Decision, Target, WorkflowErr, Workflows},
     };
 
-    use super::{Condition, Workflow};
+    use super::{Category, Condition, Workflow};
 
     #[test]
     fn parse_workflow() {
@@ -345,13 +599,13 @@ mod tests {
             conditions: vec![
                 Condition {
                     sign: super::Sign::Less,
-                    cateogry: super::Category::A,
+                    cateogry: Category::new(2, "a".into()),
                     right: 2006,
                     target: Target::Map("qkq".into()),
                 },
                 Condition {
                     sign: super::Sign::More,
-                    cateogry: super::Category::M,
+                    cateogry: Category::new(1, "m".into()),
                     right: 2090,
                     target: Target::Accept,
                 },
@@ -373,6 +627,40 @@ mod tests {
         assert_eq!(*workflow.apply(&part), Target::Map("qkq".into()));
     }
 
+    #[test]
+    fn process_missing_workflow() {
+        let workflows: Workflows = r#"in{a<2006:qkq,m>2090:A,rfg}"#.parse().unwrap();
+        let part: Part = r#"{x=787,m=2655,a=3222,s=2876}"#.parse().unwrap();
+        assert_eq!(workflows.process(&part).unwrap(), Decision::Accept);
+
+        let part: Part = r#"{x=787,m=2655,a=1222,s=2876}"#.parse().unwrap();
+        assert!(matches!(
+            workflows.process(&part),
+            Err(WorkflowErr::MissingWorkflow(name)) if name == "qkq"
+        ));
+    }
+
+    #[test]
+    fn compile_shared_workflows() {
+        // every workflow sends parts to the next one from two rules,
+        // as a plain tree it would hold 2^40 copies of the last one
+        let mut input = String::new();
+        for idx in 0..40 {
+            let (name, next) = match idx {
+                0 => ("in".to_string(), "w1".to_string()),
+                _ => (format!("w{}", idx), format!("w{}", idx + 1)),
+            };
+            input += &format!("{}{{x<{}:{},m>{}:A,{}}}\n", name, idx + 1, next, idx, next);
+        }
+        input += "w40{a<100:A,R}";
+
+        let workflows: Workflows = input.parse().unwrap();
+        let part: Part = r#"{x=50,m=0,a=5,s=0}"#.parse().unwrap();
+        assert_eq!(workflows.process(&part).unwrap(), Decision::Accept);
+        let part: Part = r#"{x=50,m=0,a=500,s=0}"#.parse().unwrap();
+        assert_eq!(workflows.process(&part).unwrap(), Decision::Reject);
+    }
+
     #[test]
     fn process_part() {
         let workflows: Workflows = r#"in{s<1351:px,qqz}
@@ -385,4 +673,155 @@ lnx{m>1548:R,A}"#
 
         assert_eq!(workflows.process(&part).unwrap(), Decision::Reject);
     }
+
+    #[test]
+    fn compile_workflows() {
+        let workflows: Workflows = r#"in{s<1351:R,qqz}
+qqz{s>2770:A,m<1801:hdj,R}
+hdj{m>838:A,R}"#
+            .parse()
+            .unwrap();
+
+        assert_eq!(
+            workflows.compile().unwrap().to_string(),
+            r#"if s < 1351 {
+    false
+} else {
+    if s > 2770 {
+        true
+    } else {
+        if m < 1801 {
+            if m > 838 {
+                true
+            } else {
+                false
+            }
+        } else {
+            false
+        }
+    }
+}
+"#
+        );
+    }
+
+    #[test]
+    fn custom_categories_and_signs() {
+        let categories = Categories::from_part("{cool=1,shiny=2}").unwrap();
+        let workflows = Workflows::parse(
+            r#"in{cool==7:A,shiny>=10:R,cool<=3:A,R}"#,
+            categories.clone(),
+        )
+        .unwrap();
+
+        let process = |part: &str| {
+            let part = categories.parse_part(part).unwrap();
+            workflows.process(&part).unwrap()
+        };
+        assert_eq!(process("{cool=7,shiny=20}"), Decision::Accept);
+        assert_eq!(process("{cool=3,shiny=10}"), Decision::Reject);
+        assert_eq!(process("{cool=3,shiny=9}"), Decision::Accept);
+        assert_eq!(process("{cool=4,shiny=9}"), Decision::Reject);
+
+        // cool==7, or cool<=3 with shiny<10
+        assert_eq!(
+            workflows.count_accepted_combinations().unwrap(),
+            4000 + 3 * 9
+        );
+
+        assert!(matches!(
+            Workflows::parse("in{x<5:A,R}", categories),
+            Err(WorkflowErr::UnknownCategory(name)) if name == "x"
+        ));
+    }
+
+    #[test]
+    fn lint_workflows() {
+        let workflows: Workflows = r#"in{s<1351:a,s<1000:R,x>4000:R,c}
+a{x>10:b,A}
+b{m<5:in,m<10:zz,R}
+c{a>0:A,a<2:R,A}
+d{x<20:A,R}"#
+            .parse()
+            .unwrap();
+
+        assert_eq!(
+            workflows.lint(),
+            vec![
+                Lint::MissingTarget {
+                    workflow: "b".into(),
+                    target: "zz".into()
+                },
+                Lint::AlwaysTrue {
+                    workflow: "c".into(),
+                    condition: "a>0:A".into()
+                },
+                Lint::NeverTrue {
+                    workflow: "c".into(),
+                    condition: "a<2:R".into()
+                },
+                Lint::NeverTrue {
+                    workflow: "in".into(),
+                    condition: "s<1000:R".into()
+                },
+                Lint::NeverTrue {
+                    workflow: "in".into(),
+                    condition: "x>4000:R".into()
+                },
+                Lint::Cycle(vec!["a".into(), "b".into(), "in".into(), "a".into()]),
+                Lint::Unreachable("d".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn lint_upstream_conditions() {
+        let workflows: Workflows = r#"in{x<100:a,R}
+a{x>200:R,m>5:b,A}
+b{x<100:A,m<3:R,A}"#
+            .parse()
+            .unwrap();
+
+        assert_eq!(
+            workflows.lint(),
+            vec![
+                Lint::NeverTrue {
+                    workflow: "a".into(),
+                    condition: "x>200:R".into()
+                },
+                Lint::AlwaysTrue {
+                    workflow: "b".into(),
+                    condition: "x<100:A".into()
+                },
+                Lint::NeverTrue {
+                    workflow: "b".into(),
+                    condition: "m<3:R".into()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn lint_missing_entry() {
+        let workflows: Workflows = r#"a{x>10:b,A}
+b{m<10:A,R}"#
+            .parse()
+            .unwrap();
+
+        assert_eq!(workflows.lint(), vec![Lint::MissingEntry]);
+    }
+
+    #[test]
+    fn compile_cycle() {
+        let workflows: Workflows = r#"in{s<1351:a,R}
+a{x>10:b,A}
+b{m<5:in,R}"#
+            .parse()
+            .unwrap();
+
+        assert!(matches!(
+            workflows.compile(),
+            Err(WorkflowErr::Cycle(path)) if path == "in -> a -> b -> in"
+        ));
+    }
 }
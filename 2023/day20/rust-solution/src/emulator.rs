@@ -6,6 +6,8 @@ use std::{
 
 const BROADCASTER: &str = "broadcaster";
 
+use crate::state::State;
+
 type RawModule = crate::modules::Module;
 
 #[derive(thiserror::Error, Debug)]
@@ -18,6 +20,9 @@ pub enum EmulatorErr {
 
     #[error("missing module named {0}")]
     MissingModule(String),
+
+    #[error("state of {0} bits does not match the network ({1} bits)")]
+    StateMismatch(usize, usize),
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -49,6 +54,30 @@ impl Module {
             }
         }
     }
+
+    // the name as it appears in the puzzle input
+    fn label(&self) -> String {
+        match self.module {
+            RawModule::FlipFlop(_) => format!("%{}", self.name),
+            RawModule::Conjunction(_) => format!("&{}", self.name),
+            RawModule::Broadcast(_) | RawModule::Unknown(_) => self.name.clone(),
+        }
+    }
+
+    fn state_len(&self) -> usize {
+        match &self.module {
+            RawModule::FlipFlop(_) => 1,
+            RawModule::Conjunction(con) => con.input_count(),
+            RawModule::Broadcast(_) | RawModule::Unknown(_) => 0,
+        }
+    }
+
+    fn status(&self) -> Option<bool> {
+        match &self.module {
+            RawModule::FlipFlop(ff) => Some(ff.status()),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -62,6 +91,13 @@ struct Pulse {
     value: bool,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StateCycle {
+    /// the number of presses before entering the cycle
+    pub start: usize,
+    pub length: usize,
+}
+
 #[derive(Debug, Default, Clone, PartialEq, Eq)]
 pub struct ExecuteResult {
     pub high: usize,
@@ -161,6 +197,154 @@ impl Emulator {
 
         unreachable!()
     }
+
+    /// Snapshots the memory of every module, in module name order
+    pub fn state(&self) -> State {
+        let mut state = State::default();
+        for module in self.modules.values() {
+            match &module.module {
+                RawModule::FlipFlop(ff) => state.push(ff.status()),
+                RawModule::Conjunction(con) => con.memory().for_each(|bit| state.push(bit)),
+                RawModule::Broadcast(_) | RawModule::Unknown(_) => {}
+            }
+        }
+
+        state
+    }
+
+    /// Restores a snapshot previously taken with [`Emulator::state`]
+    pub fn restore(&mut self, state: &State) -> Result<(), EmulatorErr> {
+        let expected = self.modules.values().map(Module::state_len).sum();
+        if state.len() != expected {
+            return Err(EmulatorErr::StateMismatch(state.len(), expected));
+        }
+
+        let mut bits = state.iter();
+        for module in self.modules.values_mut() {
+            match &mut module.module {
+                RawModule::FlipFlop(ff) => ff.set_status(bits.next().unwrap()),
+                RawModule::Conjunction(con) => {
+                    let len = con.input_count();
+                    con.set_memory(bits.by_ref().take(len))
+                }
+                RawModule::Broadcast(_) | RawModule::Unknown(_) => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Keeps clicking until the whole network repeats a previous state,
+    /// gives up after `max_presses`. The emulator is left as it was.
+    pub fn find_state_cycle(&mut self, max_presses: usize) -> Option<StateCycle> {
+        let initial = self.state();
+        let mut seen: HashMap<State, usize> = HashMap::new();
+        seen.insert(initial.clone(), 0);
+
+        let mut cycle = None;
+        for press in 1..=max_presses {
+            self.click();
+
+            let state = self.state();
+            if let Some(&start) = seen.get(&state) {
+                cycle = Some(StateCycle {
+                    start,
+                    length: press - start,
+                });
+                break;
+            }
+            seen.insert(state, press);
+        }
+
+        self.restore(&initial)
+            .expect("the initial state was taken from the same network");
+        cycle
+    }
+
+    /// Renders the network as a Graphviz DOT digraph.
+    ///
+    /// When `show_state` is set, flip-flops are filled by their current status,
+    /// so clicking the emulator N times first shows the state after N presses.
+    pub fn to_dot(&self, show_state: bool) -> String {
+        let mut dot = String::from("digraph emulator {\n");
+
+        for module in self.modules.values() {
+            let shape = match module.module {
+                RawModule::FlipFlop(_) => "box",
+                RawModule::Conjunction(_) => "invhouse",
+                RawModule::Broadcast(_) => "doublecircle",
+                RawModule::Unknown(_) => "plaintext",
+            };
+
+            let mut attrs = format!("label=\"{}\", shape={}", module.label(), shape);
+            if let Some(status) = module.status().filter(|_| show_state) {
+                let color = if status { "palegreen" } else { "lightgray" };
+                attrs += &format!(", style=filled, fillcolor={}", color);
+            }
+
+            dot += &format!("    \"{}\" [{}];\n", module.name, attrs);
+        }
+
+        for module in self.modules.values() {
+            for output in module.outputs.iter() {
+                dot += &format!("    \"{}\" -> \"{}\";\n", module.name, output);
+            }
+        }
+
+        dot += "}\n";
+        dot
+    }
+
+    /// Renders the network as a Mermaid flowchart, see [`Emulator::to_dot`].
+    pub fn to_mermaid(&self, show_state: bool) -> String {
+        let mut mermaid = String::from("flowchart LR\n");
+
+        for module in self.modules.values() {
+            let (open, close) = match module.module {
+                RawModule::FlipFlop(_) => ("[", "]"),
+                RawModule::Conjunction(_) => ("{{", "}}"),
+                RawModule::Broadcast(_) => ("((", "))"),
+                RawModule::Unknown(_) => (">", "]"),
+            };
+
+            mermaid += &format!(
+                "    {}{}\"{}\"{}\n",
+                mermaid_id(&module.name),
+                open,
+                module.label(),
+                close
+            );
+        }
+
+        for module in self.modules.values() {
+            for output in module.outputs.iter() {
+                mermaid += &format!(
+                    "    {} --> {}\n",
+                    mermaid_id(&module.name),
+                    mermaid_id(output)
+                );
+            }
+        }
+
+        if show_state {
+            mermaid += "    classDef on fill:#98fb98\n";
+            mermaid += "    classDef off fill:#d3d3d3\n";
+            for module in self.modules.values() {
+                if let Some(status) = module.status() {
+                    let class = if status { "on" } else { "off" };
+                    mermaid += &format!("    class {} {}\n", mermaid_id(&module.name), class);
+                }
+            }
+        }
+
+        mermaid
+    }
+}
+
+/// Module names can clash with Mermaid keywords such as `end`,
+/// so they are only used as labels, and prefixed to make the node ids
+fn mermaid_id(name: &str) -> String {
+    format!("m_{}", name)
 }
 
 struct ToConnect {
@@ -212,7 +396,7 @@ impl EmulatorBuilder {
             }
         }
 
-        if self.modules.get(BROADCASTER).is_none() {
+        if !self.modules.contains_key(BROADCASTER) {
             return Err(EmulatorErr::MissingBroadcast);
         }
 
@@ -258,7 +442,7 @@ impl FromStr for Emulator {
 
 #[cfg(test)]
 mod tests {
-    use super::{Emulator, EmulatorBuilder, ExecuteResult, RawModule, BROADCASTER};
+    use super::{Emulator, EmulatorBuilder, ExecuteResult, RawModule, StateCycle, BROADCASTER};
 
     #[test]
     fn parse_emulator() {
@@ -314,4 +498,129 @@ mod tests {
         check_cycle!(5, 3);
         check_cycle!(4, 2);
     }
+
+    #[test]
+    fn snapshot_and_restore() {
+        let mut emulator: Emulator = r#"broadcaster -> a
+        %a -> inv, con
+        &inv -> b
+        %b -> con
+        &con -> output"#
+            .parse()
+            .unwrap();
+
+        let initial = emulator.state();
+        // a, b, con (a, b), inv (a)
+        assert_eq!(initial.len(), 5);
+
+        emulator.click();
+        let after_one = emulator.state();
+        assert_ne!(initial.hash64(), after_one.hash64());
+
+        let mut replay = emulator.clone();
+        let expected = emulator.click();
+        replay.restore(&after_one).unwrap();
+        assert_eq!(replay.click(), expected);
+        assert_eq!(replay, emulator);
+
+        emulator.restore(&initial).unwrap();
+        assert_eq!(emulator.state(), initial);
+    }
+
+    #[test]
+    fn restore_mismatched_state() {
+        let mut emulator: Emulator = "broadcaster -> a\n%a -> output".parse().unwrap();
+        let other: Emulator = "broadcaster -> a\n%a -> b\n%b -> output".parse().unwrap();
+
+        assert!(emulator.restore(&other.state()).is_err());
+    }
+
+    #[test]
+    fn whole_network_cycle() {
+        let mut emulator: Emulator = r#"broadcaster -> a
+        %a -> inv, con
+        &inv -> b
+        %b -> con
+        &con -> output"#
+            .parse()
+            .unwrap();
+
+        let initial = emulator.clone();
+        assert_eq!(
+            emulator.find_state_cycle(100),
+            Some(StateCycle {
+                start: 0,
+                length: 4
+            })
+        );
+        assert_eq!(emulator, initial);
+    }
+
+    #[test]
+    fn render_dot() {
+        let mut emulator: Emulator = r#"broadcaster -> a
+        %a -> con
+        &con -> output"#
+            .parse()
+            .unwrap();
+        emulator.click();
+
+        assert_eq!(
+            emulator.to_dot(false),
+            r#"digraph emulator {
+    "a" [label="%a", shape=box];
+    "broadcaster" [label="broadcaster", shape=doublecircle];
+    "con" [label="&con", shape=invhouse];
+    "output" [label="output", shape=plaintext];
+    "a" -> "con";
+    "broadcaster" -> "a";
+    "con" -> "output";
+}
+"#
+        );
+
+        assert!(emulator
+            .to_dot(true)
+            .contains(r#""a" [label="%a", shape=box, style=filled, fillcolor=palegreen];"#));
+    }
+
+    #[test]
+    fn render_mermaid() {
+        let mut emulator: Emulator = r#"broadcaster -> a
+        %a -> con
+        &con -> output"#
+            .parse()
+            .unwrap();
+        emulator.click();
+        emulator.click();
+
+        assert_eq!(
+            emulator.to_mermaid(true),
+            r#"flowchart LR
+    m_a["%a"]
+    m_broadcaster(("broadcaster"))
+    m_con{{"&con"}}
+    m_output>"output"]
+    m_a --> m_con
+    m_broadcaster --> m_a
+    m_con --> m_output
+    classDef on fill:#98fb98
+    classDef off fill:#d3d3d3
+    class m_a off
+"#
+        );
+    }
+
+    #[test]
+    fn render_mermaid_keywords() {
+        let emulator: Emulator = r#"broadcaster -> end
+        %end -> graph"#
+            .parse()
+            .unwrap();
+
+        let mermaid = emulator.to_mermaid(false);
+        assert!(mermaid.contains(r#"    m_end["%end"]"#));
+        assert!(mermaid.contains("    m_broadcaster --> m_end\n"));
+        assert!(mermaid.contains("    m_end --> m_graph\n"));
+    }
 }
@@ -1,20 +1,42 @@
-use emulator::{Emulator, EmulatorErr, ExecuteResult};
+use emulator::{Emulator, EmulatorErr, ExecuteResult, StateCycle};
 
 mod emulator;
 mod modules;
+mod state;
+
+const PRESSES: usize = 1000;
+const MAX_CYCLE_PRESSES: usize = 100_000;
 
 fn part_1(input: &str) -> Result<usize, EmulatorErr> {
     let mut emulator: Emulator = input.parse()?;
     let mut result = ExecuteResult::default();
 
-    for _ in 0..10000 {
+    for _ in 0..PRESSES {
         result += emulator.click();
     }
 
+    tracing::debug!(
+        "network after {} presses (state {:016x}):\n{}",
+        PRESSES,
+        emulator.state().hash64(),
+        emulator.to_dot(true)
+    );
+    tracing::trace!(
+        "network after {} presses:\n{}",
+        PRESSES,
+        emulator.to_mermaid(true)
+    );
+
     println!("{:?}", result);
     Ok(result.low * result.high)
 }
 
+/// Looks for the first state of the whole network that repeats itself
+fn state_cycle(input: &str) -> Result<Option<StateCycle>, EmulatorErr> {
+    let mut emulator: Emulator = input.parse()?;
+    Ok(emulator.find_state_cycle(MAX_CYCLE_PRESSES))
+}
+
 fn part_2(input: &str) -> Result<usize, EmulatorErr> {
     let emulator: Emulator = input.parse()?;
 
@@ -31,7 +53,18 @@ fn part_2(input: &str) -> Result<usize, EmulatorErr> {
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     tracing_subscriber::fmt().init();
 
-    let input = rust_shared_utils::read_puzzle()?;
+    let (input, args) = rust_shared_utils::read_puzzle_with_args()?;
+    if args.iter().any(|arg| arg == "--state-cycle") {
+        match state_cycle(&input)? {
+            Some(cycle) => println!(
+                "the network repeats itself every {} presses, starting after {} presses",
+                cycle.length, cycle.start
+            ),
+            None => println!("no cycle within {} presses", MAX_CYCLE_PRESSES),
+        }
+        return Ok(());
+    }
+
     println!("part 1: {}", part_1(&input)?);
     println!("part 2: {}", part_2(&input)?);
 
@@ -48,5 +81,23 @@ mod tests {
 %c -> inv
 &inv -> a"#;
         assert_eq!(super::part_1(input).unwrap(), 32000000);
+
+        let input = r#"broadcaster -> a
+%a -> inv, con
+&inv -> b
+%b -> con
+&con -> output"#;
+        assert_eq!(super::part_1(input).unwrap(), 11687500);
+    }
+
+    #[test]
+    fn state_cycle() {
+        let input = r#"broadcaster -> a
+%a -> inv, con
+&inv -> b
+%b -> con
+&con -> output"#;
+        let cycle = super::state_cycle(input).unwrap().unwrap();
+        assert_eq!((cycle.start, cycle.length), (0, 4));
     }
 }
@@ -35,6 +35,14 @@ pub struct FlipFlop {
 }
 
 impl FlipFlop {
+    pub fn status(&self) -> bool {
+        self.status
+    }
+
+    pub fn set_status(&mut self, status: bool) {
+        self.status = status;
+    }
+
     pub fn receive(&mut self, pulse: bool) -> Option<bool> {
         if pulse {
             return None;
@@ -65,6 +73,24 @@ impl Conjunction {
     pub fn connect_input(&mut self, from: &str) {
         self.mem.insert(from.into(), false);
     }
+
+    /// The last pulse remembered for every input, ordered by input name
+    pub fn memory(&self) -> impl Iterator<Item = bool> + '_ {
+        self.mem.values().copied()
+    }
+
+    pub fn input_count(&self) -> usize {
+        self.mem.len()
+    }
+
+    /// Overwrites the memory in the same order as [`Conjunction::memory`]
+    pub fn set_memory(&mut self, mut pulses: impl Iterator<Item = bool>) {
+        for pulse in self.mem.values_mut() {
+            *pulse = pulses.next().unwrap_or(false);
+        }
+
+        self.on = self.mem.values().filter(|pulse| **pulse).count();
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -137,4 +163,19 @@ mod tests {
         assert!(!con.receive("a", true));
         assert!(con.receive("a", false));
     }
+
+    #[test]
+    fn test_conjunction_memory() {
+        let mut con = Conjunction {
+            mem: Default::default(),
+            on: 0,
+        };
+
+        con.connect_input("a");
+        con.connect_input("b");
+
+        con.set_memory([true, false].into_iter());
+        assert_eq!(con.memory().collect::<Vec<_>>(), vec![true, false]);
+        assert!(!con.receive("b", true));
+    }
 }
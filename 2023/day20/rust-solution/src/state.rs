@@ -0,0 +1,82 @@
+const FNV_OFFSET: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+/// A compact snapshot of the emulator memory:
+/// one bit per flip-flop, and one bit per conjunction input.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct State {
+    bits: Vec<u64>,
+    len: usize,
+}
+
+impl State {
+    pub fn push(&mut self, bit: bool) {
+        if self.len.is_multiple_of(64) {
+            self.bits.push(0);
+        }
+
+        if bit {
+            self.bits[self.len / 64] |= 1 << (self.len % 64);
+        }
+        self.len += 1;
+    }
+
+    pub fn get(&self, idx: usize) -> Option<bool> {
+        if idx >= self.len {
+            return None;
+        }
+
+        Some(self.bits[idx / 64] & (1 << (idx % 64)) != 0)
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = bool> + '_ {
+        (0..self.len).map(|idx| self.get(idx).unwrap())
+    }
+
+    /// A 64-bit fingerprint of the state, FNV-1a over the length and the bits,
+    /// so it stays the same across runs and Rust releases
+    pub fn hash64(&self) -> u64 {
+        let len = (self.len as u64).to_le_bytes();
+        let bits = self.bits.iter().flat_map(|word| word.to_le_bytes());
+
+        len.into_iter().chain(bits).fold(FNV_OFFSET, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(FNV_PRIME)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::State;
+
+    #[test]
+    fn push_and_get() {
+        let mut state = State::default();
+        for idx in 0..130 {
+            state.push(idx % 3 == 0);
+        }
+
+        assert_eq!(state.len(), 130);
+        assert_eq!(state.get(0), Some(true));
+        assert_eq!(state.get(64), Some(false));
+        assert_eq!(state.get(129), Some(true));
+        assert_eq!(state.get(130), None);
+        assert_eq!(state.iter().filter(|bit| *bit).count(), 44);
+    }
+
+    #[test]
+    fn stable_hash() {
+        let mut state = State::default();
+        assert_eq!(state.hash64(), 0xa8c7f832281a39c5);
+
+        state.push(false);
+        let unset = state.hash64();
+        state = State::default();
+        state.push(true);
+        assert_ne!(state.hash64(), unset);
+    }
+}
@@ -0,0 +1,111 @@
+/// The dominator tree of a DAG rooted at a virtual ground node,
+/// a node dominates another if every path from the ground to it goes through it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DominatorTree {
+    /// the immediate dominator of every node, `None` stands for the ground
+    idom: Vec<Option<usize>>,
+    children: Vec<Vec<usize>>,
+    depth: Vec<usize>,
+    size: Vec<usize>,
+}
+
+impl DominatorTree {
+    /// Builds the tree from the predecessors of every node, the nodes must
+    /// be in topological order (every predecessor comes before its successors),
+    /// and nodes without predecessors hang directly from the ground.
+    pub fn new(predecessors: &[Vec<usize>]) -> Self {
+        let len = predecessors.len();
+        let mut tree = Self {
+            idom: vec![None; len],
+            children: vec![vec![]; len],
+            depth: vec![0; len],
+            size: vec![1; len],
+        };
+
+        for (idx, preds) in predecessors.iter().enumerate() {
+            debug_assert!(preds.iter().all(|&pred| pred < idx));
+
+            let idom = preds
+                .iter()
+                .map(|&pred| Some(pred))
+                .reduce(|a, b| tree.common_dominator(a, b))
+                .flatten();
+
+            tree.idom[idx] = idom;
+            if let Some(idom) = idom {
+                tree.children[idom].push(idx);
+                tree.depth[idx] = tree.depth[idom] + 1;
+            }
+        }
+
+        // children always come after their dominator
+        for idx in (0..len).rev() {
+            if let Some(idom) = tree.idom[idx] {
+                tree.size[idom] += tree.size[idx];
+            }
+        }
+
+        tree
+    }
+
+    fn common_dominator(&self, mut a: Option<usize>, mut b: Option<usize>) -> Option<usize> {
+        while let (Some(na), Some(nb)) = (a, b) {
+            match na == nb {
+                true => return a,
+                false if self.depth[na] >= self.depth[nb] => a = self.idom[na],
+                false => b = self.idom[nb],
+            }
+        }
+
+        None
+    }
+
+    /// The number of nodes dominated by the node, excluding itself
+    pub fn dominated_count(&self, idx: usize) -> usize {
+        self.size[idx] - 1
+    }
+
+    /// The nodes dominated by the node, excluding itself
+    pub fn dominated(&self, idx: usize) -> Vec<usize> {
+        let mut dominated = vec![];
+
+        let mut frontier = self.children[idx].clone();
+        while let Some(idx) = frontier.pop() {
+            dominated.push(idx);
+            frontier.extend(self.children[idx].iter().copied());
+        }
+
+        dominated.sort_unstable();
+        dominated
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DominatorTree;
+
+    #[test]
+    fn diamond() {
+        //   3
+        //  / \
+        // 1   2
+        //  \ /
+        //   0
+        let tree = DominatorTree::new(&[vec![], vec![0], vec![0], vec![1, 2]]);
+
+        assert_eq!(tree.idom[0], None);
+        assert_eq!(tree.idom[3], Some(0));
+        assert_eq!(tree.dominated(0), vec![1, 2, 3]);
+        assert_eq!(tree.dominated_count(1), 0);
+    }
+
+    #[test]
+    fn two_roots() {
+        let tree = DominatorTree::new(&[vec![], vec![], vec![0, 1], vec![2]]);
+
+        assert_eq!(tree.idom[2], None);
+        assert_eq!(tree.idom[3], Some(2));
+        assert_eq!(tree.dominated(0), Vec::<usize>::new());
+        assert_eq!(tree.dominated(2), vec![3]);
+    }
+}
@@ -1,10 +1,25 @@
+use scenario::Step;
 use snapshot::{SnapshotErr, Stack};
 
+mod dominator;
+mod scenario;
 mod snapshot;
 
 fn part_1(input: &str) -> Result<usize, SnapshotErr> {
     let stack: Stack = input.parse()?;
+    tracing::debug!(
+        "stack before the fall:\n{}\n{}",
+        stack.project_xz(),
+        stack.project_yz()
+    );
+
     let stack = stack.after_fall();
+    tracing::debug!(
+        "stack after the fall:\n{}\n{}",
+        stack.project_xz(),
+        stack.project_yz()
+    );
+    tracing::trace!("settled stack as OBJ:\n{}", stack.to_obj());
 
     Ok(stack.count_bricks_that_can_be_disintegrated())
 }
@@ -16,8 +31,51 @@ fn part_2(input: &str) -> Result<usize, SnapshotErr> {
     Ok(stack.count_brick_that_will_fall_after_disintegration())
 }
 
+/// Lists the bricks that fall after disintegrating each brick of the settled stack
+fn chain_reactions(input: &str) -> Result<Vec<(usize, Vec<usize>)>, SnapshotErr> {
+    let stack: Stack = input.parse()?;
+    let stack = stack.after_fall();
+
+    Ok(stack
+        .bricks()
+        .map(|idx| (idx, stack.chain_reaction(idx)))
+        .filter(|(_, fall)| !fall.is_empty())
+        .collect())
+}
+
 fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let input = rust_shared_utils::read_puzzle()?;
+    tracing_subscriber::fmt::init();
+    let (input, args) = rust_shared_utils::read_puzzle_with_args()?;
+    if args.iter().any(|arg| arg == "--chain-reactions") {
+        for (idx, fall) in chain_reactions(&input)? {
+            let fall = fall.iter().map(|idx| idx.to_string()).collect::<Vec<_>>();
+            println!(
+                "brick {} makes {} fall: {}",
+                idx,
+                fall.len(),
+                fall.join(", ")
+            );
+        }
+        return Ok(());
+    }
+
+    // plays the steps read from stdin on the settled stack, one per line
+    if args.iter().any(|arg| arg == "--scenario") {
+        let mut stack = input.parse::<Stack>()?.after_fall();
+        for line in std::io::stdin().lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            match line.parse::<Step>().and_then(|step| step.apply(&mut stack)) {
+                Ok(out) => println!("{}", out),
+                Err(err) => println!("error: {}", err),
+            }
+        }
+        return Ok(());
+    }
+
     println!("part 1: {}", part_1(&input)?);
     println!("part 2: {}", part_2(&input)?);
 
@@ -42,4 +100,12 @@ mod tests {
     fn part_2() {
         assert_eq!(super::part_2(INPUT).unwrap(), 7);
     }
+
+    #[test]
+    fn chain_reactions() {
+        assert_eq!(
+            super::chain_reactions(INPUT).unwrap(),
+            vec![(0, vec![1, 2, 3, 4, 5, 6]), (5, vec![6])]
+        );
+    }
 }
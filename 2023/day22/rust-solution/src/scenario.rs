@@ -0,0 +1,82 @@
+use std::str::FromStr;
+
+use crate::snapshot::{Brick, SnapshotErr, Stack};
+
+/// A step of a "what if" scenario, played on a settled stack
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Step {
+    /// `pull <index>` pulls the brick out, and lets everything above it fall
+    Pull(usize),
+    /// `drop <brick>` drops a new brick, written as in the puzzle, from above the stack
+    Drop(Brick),
+    /// `show` draws both side views of the stack
+    Show,
+}
+
+impl Step {
+    /// Plays the step on the stack, and describes what happened
+    pub fn apply(&self, stack: &mut Stack) -> Result<String, SnapshotErr> {
+        match self {
+            Self::Pull(idx) => {
+                let falls = stack.remove(*idx)?;
+                if falls.is_empty() {
+                    return Ok(format!("pulled brick {}, nothing fell", idx));
+                }
+
+                let falls = falls
+                    .iter()
+                    .map(|fall| format!("{} by {}", fall.brick, fall.distance))
+                    .collect::<Vec<_>>();
+                Ok(format!("pulled brick {}, fell: {}", idx, falls.join(", ")))
+            }
+            Self::Drop(brick) => Ok(format!("dropped brick {}", stack.drop(brick.clone()))),
+            Self::Show => Ok(format!("{}\n{}", stack.project_xz(), stack.project_yz())),
+        }
+    }
+}
+
+impl FromStr for Step {
+    type Err = SnapshotErr;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (command, arg) = s.trim().split_once(' ').unwrap_or((s.trim(), ""));
+        match command {
+            "pull" => arg
+                .trim()
+                .parse()
+                .map(Self::Pull)
+                .map_err(|_| SnapshotErr::BadStep(s.into())),
+            "drop" => arg.parse().map(Self::Drop),
+            "show" => Ok(Self::Show),
+            _ => Err(SnapshotErr::BadStep(s.into())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::snapshot::{SnapshotErr, Stack};
+
+    use super::Step;
+
+    #[test]
+    fn play_scenario() {
+        let mut stack = r#"1,0,1~1,2,1
+0,0,2~2,0,2
+0,2,3~2,2,3
+0,0,4~0,2,4
+2,0,5~2,2,5
+0,1,6~2,1,6
+1,1,8~1,1,9"#
+            .parse::<Stack>()
+            .unwrap()
+            .after_fall();
+
+        let mut play = |step: &str| step.parse::<Step>()?.apply(&mut stack);
+        assert_eq!(play("pull 5").unwrap(), "pulled brick 5, fell: 6 by 3");
+        assert_eq!(play("pull 1").unwrap(), "pulled brick 1, nothing fell");
+        assert_eq!(play("drop 1,1,1~1,1,2").unwrap(), "dropped brick 7");
+        assert!(matches!(play("pull 5"), Err(SnapshotErr::MissingBrick(5))));
+        assert!(matches!(play("push 5"), Err(SnapshotErr::BadStep(_))));
+    }
+}
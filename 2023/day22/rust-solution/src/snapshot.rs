@@ -1,9 +1,12 @@
 use std::{
-    collections::{HashMap, HashSet},
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, HashSet},
     ops::Range,
     str::FromStr,
 };
 
+use crate::dominator::DominatorTree;
+
 #[derive(thiserror::Error, Debug)]
 pub enum SnapshotErr {
     #[error("unknown brick format: {0}")]
@@ -14,6 +17,12 @@ pub enum SnapshotErr {
 
     #[error("a position should be represented by 3 coordinates")]
     MissingCoordinates,
+
+    #[error("there is no brick with index {0}")]
+    MissingBrick(usize),
+
+    #[error("unknown scenario step: {0}")]
+    BadStep(String),
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -30,7 +39,7 @@ impl Point {
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
-struct Brick {
+pub struct Brick {
     x_range: Range<usize>,
     y_range: Range<usize>,
     z_range: Range<usize>,
@@ -38,25 +47,43 @@ struct Brick {
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Stack {
-    bricks: Vec<Brick>,
+    // removed bricks leave a hole behind, so the indices stay stable
+    bricks: Vec<Option<Brick>>,
+    supports: Vec<Support>,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
 struct Support {
     support: HashSet<usize>,
     supported_by: HashSet<usize>,
 }
 
+/// A brick that fell down during an edit of the stack
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Fall {
+    pub brick: usize,
+    pub distance: usize,
+}
+
 impl Stack {
     fn new(mut bricks: Vec<Brick>) -> Self {
-        bricks.sort_by(|a, b| a.z_range.end.cmp(&b.z_range.end));
+        // a brick always comes after the bricks supporting it
+        bricks.sort_by_key(|brick| brick.z_range.end);
 
-        Self { bricks }
+        let supports = Self::build_support_vec(&bricks);
+        Self {
+            bricks: bricks.into_iter().map(Some).collect(),
+            supports,
+        }
     }
 
-    pub fn after_fall(mut self) -> Self {
+    /// Settles the stack from scratch, the bricks are re-indexed
+    pub fn after_fall(self) -> Self {
+        let mut bricks = self.bricks.into_iter().flatten().collect::<Vec<_>>();
+        bricks.sort_by_key(|brick| brick.z_range.start);
+
         let mut zmap: HashMap<(usize, usize), usize> = Default::default();
-        for brick in self.bricks.iter_mut() {
+        for brick in bricks.iter_mut() {
             let xy_map = brick
                 .x_range
                 .clone()
@@ -80,66 +107,180 @@ impl Stack {
             });
         }
 
-        Self::new(self.bricks)
+        Self::new(bricks)
     }
 
-    pub fn count_bricks_that_can_be_disintegrated(&self) -> usize {
-        let supports = self.build_support_vec();
-
-        supports
+    fn live_bricks(&self) -> impl Iterator<Item = (usize, &Brick)> {
+        self.bricks
             .iter()
-            .filter(|support| {
-                support
+            .enumerate()
+            .filter_map(|(idx, brick)| brick.as_ref().map(|brick| (idx, brick)))
+    }
+
+    /// The indices of the bricks in the stack
+    pub fn bricks(&self) -> impl Iterator<Item = usize> + '_ {
+        self.live_bricks().map(|(idx, _)| idx)
+    }
+
+    pub fn count_bricks_that_can_be_disintegrated(&self) -> usize {
+        self.live_bricks()
+            .filter(|(idx, _)| {
+                self.supports[*idx]
                     .support
                     .iter()
-                    .all(|&idx| supports[idx].supported_by.len() > 1)
+                    .all(|&sidx| self.supports[sidx].supported_by.len() > 1)
             })
             .count()
     }
 
     pub fn count_brick_that_will_fall_after_disintegration(&self) -> usize {
-        let supports = self.build_support_vec();
-
-        (0..self.bricks.len())
-            .map(|idx| {
-                self.count_brick_that_will_fall_after_disintegration_of_specific_block(
-                    supports.clone(),
-                    idx,
-                )
-            })
-            .sum()
+        let (tree, order) = self.build_dominator_tree();
+
+        (0..order.len()).map(|pos| tree.dominated_count(pos)).sum()
     }
 
-    fn count_brick_that_will_fall_after_disintegration_of_specific_block(
-        &self,
-        mut supports: Vec<Support>,
-        block_idx: usize,
-    ) -> usize {
-        let mut frontier = supports[block_idx]
-            .support
+    /// The bricks that will fall if the brick is disintegrated
+    pub fn chain_reaction(&self, brick_idx: usize) -> Vec<usize> {
+        let (tree, order) = self.build_dominator_tree();
+        let Some(pos) = order.iter().position(|&idx| idx == brick_idx) else {
+            return vec![];
+        };
+
+        let mut fall = tree
+            .dominated(pos)
+            .into_iter()
+            .map(|pos| order[pos])
+            .collect::<Vec<_>>();
+        fall.sort_unstable();
+        fall
+    }
+
+    // a brick falls iff every path from the ground to it
+    // goes through the disintegrated brick, i.e. it is dominated by it.
+    // the tree is indexed by the position in the returned brick order
+    fn build_dominator_tree(&self) -> (DominatorTree, Vec<usize>) {
+        let mut order = self.live_bricks().collect::<Vec<_>>();
+        order.sort_by_key(|(_, brick)| brick.z_range.end);
+        let order = order.into_iter().map(|(idx, _)| idx).collect::<Vec<_>>();
+
+        let mut positions = vec![0; self.bricks.len()];
+        for (pos, &idx) in order.iter().enumerate() {
+            positions[idx] = pos;
+        }
+
+        let supported_by = order
             .iter()
-            .filter(|&&idx| supports[idx].supported_by.len() == 1)
-            .cloned()
+            .map(|&idx| {
+                self.supports[idx]
+                    .supported_by
+                    .iter()
+                    .map(|&sidx| positions[sidx])
+                    .collect::<Vec<_>>()
+            })
             .collect::<Vec<_>>();
 
-        let mut fall = 0;
-        while let Some(idx) = frontier.pop() {
-            for sidx in supports[idx].support.clone().into_iter() {
-                supports[sidx].supported_by.remove(&idx);
-                if supports[sidx].supported_by.is_empty() {
-                    frontier.push(sidx);
+        (DominatorTree::new(&supported_by), order)
+    }
+
+    /// The side view of the stack from the y axis, as drawn in the puzzle
+    pub fn project_xz(&self) -> String {
+        self.project('x', |brick| brick.x_range.clone())
+    }
+
+    /// The side view of the stack from the x axis, as drawn in the puzzle
+    pub fn project_yz(&self) -> String {
+        self.project('y', |brick| brick.y_range.clone())
+    }
+
+    fn project(&self, axis: char, range: impl Fn(&Brick) -> Range<usize>) -> String {
+        let width = self
+            .live_bricks()
+            .map(|(_, brick)| range(brick).end)
+            .max()
+            .unwrap_or(0);
+        let height = self
+            .live_bricks()
+            .map(|(_, brick)| brick.z_range.end - 1)
+            .max()
+            .unwrap_or(0);
+
+        // the brick seen at every cell, or `None` if more than one is seen
+        let mut view: HashMap<(usize, usize), Option<usize>> = HashMap::new();
+        for (idx, brick) in self.live_bricks() {
+            for a in range(brick) {
+                for z in brick.z_range.clone() {
+                    view.entry((a, z))
+                        .and_modify(|seen| {
+                            if *seen != Some(idx) {
+                                *seen = None
+                            }
+                        })
+                        .or_insert(Some(idx));
                 }
             }
+        }
 
-            fall += 1;
+        let mut out = format!("{}{}\n", " ".repeat(width / 2), axis);
+        out += &(0..width)
+            .map(|a| char::from_digit((a % 10) as u32, 10).unwrap())
+            .collect::<String>();
+        out += "\n";
+
+        for z in (1..=height).rev() {
+            out += &(0..width)
+                .map(|a| match view.get(&(a, z)) {
+                    Some(Some(idx)) => brick_label(*idx),
+                    Some(None) => '?',
+                    None => '.',
+                })
+                .collect::<String>();
+
+            out += &format!(" {}", z);
+            if z == height.div_ceil(2) {
+                out += " z";
+            }
+            out += "\n";
         }
 
-        fall
+        out += &format!("{} 0\n", "-".repeat(width));
+        out
     }
 
-    fn build_support_vec(&self) -> Vec<Support> {
+    /// Exports the bricks as boxes in the Wavefront OBJ format
+    pub fn to_obj(&self) -> String {
+        const FACES: [[usize; 4]; 6] = [
+            [1, 2, 4, 3],
+            [5, 7, 8, 6],
+            [1, 5, 6, 2],
+            [3, 4, 8, 7],
+            [1, 3, 7, 5],
+            [2, 6, 8, 4],
+        ];
+
+        let mut out = String::new();
+        for (count, (idx, brick)) in self.live_bricks().enumerate() {
+            out += &format!("o brick_{}\n", idx);
+            for x in [brick.x_range.start, brick.x_range.end] {
+                for y in [brick.y_range.start, brick.y_range.end] {
+                    for z in [brick.z_range.start, brick.z_range.end] {
+                        out += &format!("v {} {} {}\n", x, y, z);
+                    }
+                }
+            }
+
+            let offset = count * 8;
+            for face in FACES {
+                let face = face.map(|vertex| (vertex + offset).to_string());
+                out += &format!("f {}\n", face.join(" "));
+            }
+        }
+
+        out
+    }
+
+    fn build_support_vec(bricks: &[Brick]) -> Vec<Support> {
         let mut map = HashMap::new();
-        for (idx, brick) in self.bricks.iter().enumerate() {
+        for (idx, brick) in bricks.iter().enumerate() {
             for x in brick.x_range.clone() {
                 for y in brick.y_range.clone() {
                     for z in brick.z_range.clone() {
@@ -149,13 +290,8 @@ impl Stack {
             }
         }
 
-        let mut supports = (0..self.bricks.len())
-            .map(|_| Support {
-                support: Default::default(),
-                supported_by: Default::default(),
-            })
-            .collect::<Vec<_>>();
-        for (idx, brick) in self.bricks.iter().enumerate() {
+        let mut supports = vec![Support::default(); bricks.len()];
+        for (idx, brick) in bricks.iter().enumerate() {
             for x in brick.x_range.clone() {
                 for y in brick.y_range.clone() {
                     if let Some(&sidx) = map.get(&Point::new(x, y, brick.z_range.end)) {
@@ -170,6 +306,108 @@ impl Stack {
     }
 }
 
+/// Edits of a settled stack
+impl Stack {
+    /// Pulls the brick out of the stack, and lets everything above it fall.
+    /// The indices of the other bricks are left untouched.
+    pub fn remove(&mut self, idx: usize) -> Result<Vec<Fall>, SnapshotErr> {
+        self.bricks
+            .get_mut(idx)
+            .and_then(Option::take)
+            .ok_or(SnapshotErr::MissingBrick(idx))?;
+
+        let mut frontier = BinaryHeap::new();
+        for sidx in self.detach(idx) {
+            let brick = self.bricks[sidx].as_ref().unwrap();
+            frontier.push(Reverse((brick.z_range.start, sidx)));
+        }
+
+        // bricks are settled from the bottom up, so every brick
+        // lands on bricks that have already settled
+        let mut falls = vec![];
+        while let Some(Reverse((_, fidx))) = frontier.pop() {
+            for sidx in self.detach(fidx) {
+                let brick = self.bricks[sidx].as_ref().unwrap();
+                frontier.push(Reverse((brick.z_range.start, sidx)));
+            }
+
+            let brick = self.bricks[fidx].take().unwrap();
+            let (brick, distance) = self.settle(brick, fidx);
+            self.bricks[fidx] = Some(brick);
+
+            falls.push(Fall {
+                brick: fidx,
+                distance,
+            });
+        }
+
+        falls.sort_unstable_by_key(|fall| fall.brick);
+        Ok(falls)
+    }
+
+    /// Drops a new brick from above the stack, and returns its index
+    pub fn drop(&mut self, brick: Brick) -> usize {
+        let idx = self.bricks.len();
+        self.supports.push(Support::default());
+
+        let height = brick.z_range.len();
+        let mut brick = brick;
+        brick.z_range = usize::MAX - height..usize::MAX;
+
+        let (brick, _) = self.settle(brick, idx);
+        self.bricks.push(Some(brick));
+
+        idx
+    }
+
+    // removes the brick from the support graph, returns
+    // the bricks that were left without any support
+    fn detach(&mut self, idx: usize) -> Vec<usize> {
+        let support = std::mem::take(&mut self.supports[idx]);
+        for &sidx in support.supported_by.iter() {
+            self.supports[sidx].support.remove(&idx);
+        }
+
+        support
+            .support
+            .into_iter()
+            .filter(|&sidx| {
+                let supported_by = &mut self.supports[sidx].supported_by;
+                supported_by.remove(&idx);
+                supported_by.is_empty()
+            })
+            .collect()
+    }
+
+    // moves the (detached) brick down until it hits the ground or
+    // another brick below it, and connects it to its new supporters
+    fn settle(&mut self, mut brick: Brick, idx: usize) -> (Brick, usize) {
+        let below = self
+            .live_bricks()
+            .filter(|(_, other)| {
+                other.z_range.end <= brick.z_range.start && other.overlaps_xy(&brick)
+            })
+            .map(|(sidx, other)| (sidx, other.z_range.end))
+            .collect::<Vec<_>>();
+
+        let floor = below.iter().map(|(_, z)| *z).max().unwrap_or(1);
+        let distance = brick.z_range.start - floor;
+        brick.z_range = floor..(brick.z_range.end - distance);
+
+        for (sidx, _) in below.into_iter().filter(|(_, z)| *z == floor) {
+            self.supports[sidx].support.insert(idx);
+            self.supports[idx].supported_by.insert(sidx);
+        }
+
+        (brick, distance)
+    }
+}
+
+// bricks are named by letters in the puzzle
+fn brick_label(idx: usize) -> char {
+    (b'A' + (idx % 26) as u8) as char
+}
+
 impl Brick {
     fn new(start: Point, end: Point) -> Self {
         Self {
@@ -178,6 +416,13 @@ impl Brick {
             z_range: (start.z.min(end.z)..(start.z.max(end.z) + 1)),
         }
     }
+
+    fn overlaps_xy(&self, other: &Brick) -> bool {
+        self.x_range.start < other.x_range.end
+            && other.x_range.start < self.x_range.end
+            && self.y_range.start < other.y_range.end
+            && other.y_range.start < self.y_range.end
+    }
 }
 
 impl FromStr for Point {
@@ -225,3 +470,153 @@ impl FromStr for Stack {
         Ok(Self::new(bricks))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{Brick, Fall, Stack};
+
+    fn example() -> Stack {
+        r#"1,0,1~1,2,1
+0,0,2~2,0,2
+0,2,3~2,2,3
+0,0,4~0,2,4
+2,0,5~2,2,5
+0,1,6~2,1,6
+1,1,8~1,1,9"#
+            .parse()
+            .unwrap()
+    }
+
+    #[test]
+    fn chain_reaction() {
+        let stack = example().after_fall();
+
+        // A takes everything else down, F takes only G
+        assert_eq!(stack.chain_reaction(0), vec![1, 2, 3, 4, 5, 6]);
+        assert_eq!(stack.chain_reaction(5), vec![6]);
+        assert_eq!(stack.chain_reaction(1), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn side_projections() {
+        let stack = example();
+
+        assert_eq!(
+            stack.project_xz(),
+            r#" x
+012
+.G. 9
+.G. 8
+... 7
+FFF 6
+..E 5 z
+D.. 4
+CCC 3
+BBB 2
+.A. 1
+--- 0
+"#
+        );
+
+        assert_eq!(
+            stack.project_yz(),
+            r#" y
+012
+.G. 9
+.G. 8
+... 7
+.F. 6
+EEE 5 z
+DDD 4
+..C 3
+B.. 2
+AAA 1
+--- 0
+"#
+        );
+
+        let settled = stack.after_fall();
+        assert_eq!(
+            settled.project_xz(),
+            r#" x
+012
+.G. 6
+.G. 5
+FFF 4
+D.E 3 z
+??? 2
+.A. 1
+--- 0
+"#
+        );
+    }
+
+    #[test]
+    fn export_obj() {
+        let stack: Stack = "1,0,1~1,2,1".parse().unwrap();
+        let obj = stack.to_obj();
+
+        assert!(obj.starts_with("o brick_0\nv 1 0 1\nv 1 0 2\nv 1 3 1\n"));
+        assert_eq!(obj.lines().filter(|line| line.starts_with("v ")).count(), 8);
+        assert!(obj.ends_with("f 2 6 8 4\n"));
+    }
+
+    #[test]
+    fn remove_bricks() {
+        let mut stack = example().after_fall();
+
+        // F is the only one holding G
+        let falls = stack.remove(5).unwrap();
+        assert_eq!(
+            falls,
+            vec![Fall {
+                brick: 6,
+                distance: 3
+            }]
+        );
+        assert!(stack.remove(5).is_err());
+
+        // B and C hold D and E together
+        assert_eq!(stack.remove(1).unwrap(), vec![]);
+        let falls = stack.remove(2).unwrap();
+        assert_eq!(
+            falls,
+            vec![
+                Fall {
+                    brick: 3,
+                    distance: 2
+                },
+                Fall {
+                    brick: 4,
+                    distance: 2
+                },
+            ]
+        );
+
+        // only A, D, E and G are left, G still rests on A
+        let mut settled = stack.clone().after_fall();
+        assert_eq!(settled.count_bricks_that_can_be_disintegrated(), 3);
+        assert_eq!(
+            settled.remove(0).unwrap(),
+            vec![Fall {
+                brick: 3,
+                distance: 1
+            }]
+        );
+    }
+
+    #[test]
+    fn drop_bricks() {
+        let mut stack = example().after_fall();
+
+        let idx = stack.drop("1,1,1~1,1,2".parse::<Brick>().unwrap());
+        assert_eq!(idx, 7);
+        assert_eq!(stack.chain_reaction(6), vec![7]);
+
+        // the new brick lands on D, next to F
+        let idx = stack.drop("0,0,1~0,0,1".parse::<Brick>().unwrap());
+        assert_eq!(stack.chain_reaction(3), vec![idx]);
+        assert_eq!(stack.chain_reaction(5), vec![6, 7]);
+        assert_eq!(stack.remove(idx).unwrap(), vec![]);
+    }
+}
@@ -11,3 +11,16 @@ pub fn read_puzzle() -> std::io::Result<String> {
 
     std::fs::read_to_string(&args[1])
 }
+
+/// Reads the input file like `read_puzzle`, along with the arguments that follow it
+pub fn read_puzzle_with_args() -> std::io::Result<(String, Vec<String>)> {
+    let mut args = std::env::args().skip(1);
+    let Some(path) = args.next() else {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            "please provide an input file",
+        ));
+    };
+
+    Ok((std::fs::read_to_string(path)?, args.collect()))
+}